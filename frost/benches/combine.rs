@@ -0,0 +1,76 @@
+//! Compares `combine_sigs` with `combine_sigs_prepared` for a coordinator that combines many
+//! signatures under the same key
+//!
+//! Run with `cargo bench --bench combine`.
+
+use std::time::Instant;
+
+use curve25519_dalek::{edwards::EdwardsPoint, scalar::Scalar};
+use frost::sign::{client::*, server::*, PreparedCombiner};
+use rand::rngs::OsRng;
+use sha2::Sha512;
+
+const ITERATIONS: u32 = 1000;
+
+#[allow(non_snake_case)]
+fn main() {
+    let p_client = Scalar::random(&mut OsRng);
+    let p_server = Scalar::random(&mut OsRng);
+    let P_server = EdwardsPoint::mul_base(&p_server);
+    let P_joint = (EdwardsPoint::mul_base(&p_client) + P_server).compress();
+    let P_server = P_server.compress();
+    let message = b"sample message";
+
+    let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+    let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+    let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+        &p_client,
+        &P_joint,
+        message,
+        &d_client,
+        &e_client,
+        &client_sign_message_1,
+        &server_sign_message_1,
+    )
+    .unwrap();
+    let (_, server_sign_message_2) = ServerSign::second_round::<Sha512>(
+        &p_server,
+        &P_joint,
+        message,
+        &d_server,
+        &e_server,
+        &client_sign_message_1,
+        &server_sign_message_1,
+    )
+    .unwrap();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        ClientSign::combine_sigs::<Sha512>(
+            &P_joint,
+            &P_server,
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+    }
+    println!("combine_sigs:          {:?}", start.elapsed() / ITERATIONS);
+
+    let combiner = PreparedCombiner::new(&P_joint, &P_server).unwrap();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        ClientSign::combine_sigs_prepared::<Sha512>(
+            &combiner,
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+    }
+    println!("combine_sigs_prepared: {:?}", start.elapsed() / ITERATIONS);
+}
@@ -0,0 +1,23 @@
+//! Writes the test vectors of a protocol run as JSON
+//!
+//! Usage: `cargo run --example gen-vectors [seed-byte] [message]`. The seed is the given byte
+//! repeated 32 times and defaults to zero; the message defaults to `sample message`.
+
+use frost::vectors::conformance_vectors;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let seed_byte = args
+        .next()
+        .map(|arg| {
+            arg.parse::<u8>()
+                .expect("the seed byte is a number from 0 to 255")
+        })
+        .unwrap_or(0);
+    let message = args.next().unwrap_or_else(|| "sample message".to_string());
+
+    print!(
+        "{}",
+        conformance_vectors([seed_byte; 32], message.as_bytes())
+    );
+}
@@ -0,0 +1,38 @@
+//! Base58 addresses of joint public keys, as used by Solana and similar chains
+//!
+//! This module is only compiled with the `base58` feature.
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum AddressError {
+    #[error("the address is not valid base58")]
+    Base58,
+    #[error("the address does not decode to 32 bytes")]
+    Length,
+    #[error("compressed curve point failed to decompress")]
+    Decompression,
+}
+
+/// Encodes the joint public key as a base58 address
+#[allow(non_snake_case)]
+pub fn joint_key_to_base58(P_joint: &CompressedEdwardsY) -> String {
+    bs58::encode(P_joint.as_bytes()).into_string()
+}
+
+/// Decodes a base58 address into the joint public key it encodes
+///
+/// The address must decode to exactly 32 bytes that are a valid curve point.
+pub fn joint_key_from_base58(address: &str) -> Result<CompressedEdwardsY, AddressError> {
+    let bytes = bs58::decode(address)
+        .into_vec()
+        .map_err(|_| AddressError::Base58)?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| AddressError::Length)?;
+
+    #[allow(non_snake_case)]
+    let P_joint = CompressedEdwardsY(bytes);
+    P_joint.decompress().ok_or(AddressError::Decompression)?;
+
+    Ok(P_joint)
+}
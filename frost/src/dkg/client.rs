@@ -1,4 +1,8 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Display, Formatter},
+    str::FromStr,
+};
 
 use base64::{prelude::BASE64_STANDARD, Engine};
 use curve25519_dalek::{
@@ -6,17 +10,22 @@ use curve25519_dalek::{
     edwards::{CompressedEdwardsY, EdwardsPoint},
     scalar::Scalar,
 };
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use sha2::Sha512;
 
 use crate::dkg::{
+    check_commitments, prove_knowledge, round1_commitment,
     server::{DkgServerRound1, DkgServerRound2},
-    DkgError,
+    verify_knowledge, DkgError, DkgOutput, Phase, ProofOfKnowledge, Round1Commitment, CLIENT_LABEL,
+    SERVER_LABEL,
 };
+use crate::wire::{base64_segments, point_at, scalar_at};
+use crate::{Party, Redacted};
 
 /// The message that the client sends over to the server at round 1 of the distributed key
 /// generation protocol
 #[allow(non_snake_case)]
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct DkgClientRound1 {
     pub C0: CompressedEdwardsY,
     pub C1: CompressedEdwardsY,
@@ -24,6 +33,16 @@ pub struct DkgClientRound1 {
     pub mu: Scalar,
 }
 
+impl DkgClientRound1 {
+    /// The proof of knowledge over `C0`, detached from the commitments
+    pub fn proof_of_knowledge(&self) -> ProofOfKnowledge {
+        ProofOfKnowledge {
+            R: self.R,
+            mu: self.mu,
+        }
+    }
+}
+
 impl Display for DkgClientRound1 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{}", BASE64_STANDARD.encode(self.C0.as_bytes()))?;
@@ -33,20 +52,122 @@ impl Display for DkgClientRound1 {
     }
 }
 
+impl FromStr for DkgClientRound1 {
+    type Err = DkgError;
+
+    /// Decodes a message from the base64 form written by its `Display` impl
+    fn from_str(s: &str) -> Result<Self, DkgError> {
+        let bytes = base64_segments(s, &[32; 4]).ok_or(DkgError::MalformedMessage)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Orders the messages by their byte encodings, so that they can be used as keys of a `BTreeMap`
+impl Ord for DkgClientRound1 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_bytes().cmp(&other.to_bytes())
+    }
+}
+
+impl PartialOrd for DkgClientRound1 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl DkgClientRound1 {
+    /// The length of the byte encoding of the message
+    pub const LENGTH: usize = 128;
+
+    /// Encodes the message as `C0 || C1 || R || mu`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::LENGTH);
+        bytes.extend_from_slice(self.C0.as_bytes());
+        bytes.extend_from_slice(self.C1.as_bytes());
+        bytes.extend_from_slice(self.R.as_bytes());
+        bytes.extend_from_slice(self.mu.as_bytes());
+        bytes
+    }
+
+    /// The commitment to the message for the commit-then-reveal flow
+    pub fn commitment<CtxDigest>(&self) -> Round1Commitment
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        round1_commitment::<CtxDigest>(Party::Client, &self.to_bytes())
+    }
+
+    /// Decodes a message encoded by `to_bytes`
+    ///
+    /// The points are not decompressed here, so an invalid point is only caught by the round that
+    /// uses it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(DkgError::MalformedMessage);
+        }
+
+        Ok(Self {
+            C0: point_at(bytes, 0),
+            C1: point_at(bytes, 32),
+            R: point_at(bytes, 64),
+            mu: scalar_at(bytes, 96).ok_or(DkgError::NonCanonicalScalar)?,
+        })
+    }
+}
+
 /// The message that the client sends over to the server at round 1 of the distributed key
 /// generation protocol
 #[allow(non_snake_case)]
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct DkgClientRound2 {
     pub c_server: Scalar,
 }
 
+impl Debug for DkgClientRound2 {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("DkgClientRound2")
+            .field("c_server", &Redacted)
+            .finish()
+    }
+}
+
 impl Display for DkgClientRound2 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{}", BASE64_STANDARD.encode(self.c_server.as_bytes()))
     }
 }
 
+impl FromStr for DkgClientRound2 {
+    type Err = DkgError;
+
+    /// Decodes a message from the base64 form written by its `Display` impl
+    fn from_str(s: &str) -> Result<Self, DkgError> {
+        let bytes = base64_segments(s, &[32]).ok_or(DkgError::MalformedMessage)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl DkgClientRound2 {
+    /// The length of the byte encoding of the message
+    pub const LENGTH: usize = 32;
+
+    /// Encodes the message as `c_server`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.c_server.as_bytes().to_vec()
+    }
+
+    /// Decodes a message encoded by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(DkgError::MalformedMessage);
+        }
+
+        Ok(Self {
+            c_server: scalar_at(bytes, 0).ok_or(DkgError::NonCanonicalScalar)?,
+        })
+    }
+}
+
 pub struct ClientDkg;
 #[allow(non_snake_case)]
 impl ClientDkg {
@@ -55,33 +176,59 @@ impl ClientDkg {
     /// It does not matter whether the client or the server starts the protocol first.
     pub fn start_first_round<CtxDigest>(
     ) -> (Scalar, Scalar, EdwardsPoint, EdwardsPoint, DkgClientRound1)
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        Self::start_first_round_with_rng::<CtxDigest>(&mut OsRng)
+    }
+
+    /// The client logic that starts the first round of the distributed key generation protocol,
+    /// drawing its randomness from `rng`
+    pub fn start_first_round_with_rng<CtxDigest>(
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (Scalar, Scalar, EdwardsPoint, EdwardsPoint, DkgClientRound1)
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        Self::start_first_round_with_session_id::<CtxDigest>(rng, &[])
+    }
+
+    /// The client logic that starts the first round of the distributed key generation protocol,
+    /// binding the client's proof of knowledge to `session_id`
+    ///
+    /// `session_id` identifies this run of the protocol, such as a value that both parties agreed
+    /// on beforehand. The server must verify the proof with `finalize_first_round_with_session_id`
+    /// and the same `session_id`, so a round 1 message cannot be replayed into another session.
+    pub fn start_first_round_with_session_id<CtxDigest>(
+        rng: &mut (impl RngCore + CryptoRng),
+        session_id: &[u8],
+    ) -> (Scalar, Scalar, EdwardsPoint, EdwardsPoint, DkgClientRound1)
     where
         CtxDigest: Digest<OutputSize = U64>,
     {
         // 1. Generates two random scalar elements
-        let c0 = Scalar::random(&mut OsRng);
-        let c1 = Scalar::random(&mut OsRng);
+        let c0 = Scalar::random(rng);
+        let c1 = Scalar::random(rng);
 
         // 2. Commits to the two scalar elements above as elliptic curve points
         let C0 = EdwardsPoint::mul_base(&c0);
         let C1 = EdwardsPoint::mul_base(&c1);
 
         // 3. Create a proof of knowledge of `c0` over `C0`
-        let k = Scalar::random(&mut OsRng);
-        let R = EdwardsPoint::mul_base(&k);
-
-        let mut h = CtxDigest::new();
-        h.update(b"client");
-        h.update(C0.compress().as_bytes());
-        h.update(R.compress().as_bytes());
-        let c = Scalar::from_hash(h);
-        let mu = k + c0 * c;
+        let (R, mu) = prove_knowledge::<CtxDigest>(
+            rng,
+            CLIENT_LABEL,
+            Phase::Dkg,
+            &c0,
+            &C0.compress(),
+            session_id,
+        );
 
         // 4. Construct the client's message to the server
         let client_message = DkgClientRound1 {
             C0: C0.compress(),
             C1: C1.compress(),
-            R: R.compress(),
+            R,
             mu,
         };
 
@@ -94,24 +241,94 @@ impl ClientDkg {
     where
         CtxDigest: Digest<OutputSize = U64>,
     {
+        Self::finalize_first_round_with_session_id::<CtxDigest>(server_message, &[])
+    }
+
+    /// The client logic that verifies the server's message in the first round of the distributed
+    /// key generation protocol, whose proof of knowledge must be bound to `session_id`
+    pub fn finalize_first_round_with_session_id<CtxDigest>(
+        server_message: &DkgServerRound1,
+        session_id: &[u8],
+    ) -> Result<(), DkgError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        #[cfg(feature = "fault-injection")]
+        crate::fault::take_dkg_fault(crate::fault::DkgStep::ClientFinalizeFirstRound)?;
+
         let DkgServerRound1 { S0, S1: _, R, mu } = server_message;
 
         // verify the server's proof of knowledge
-        let mut h = CtxDigest::new();
-        h.update(b"server");
-        h.update(S0.as_bytes());
-        h.update(R.as_bytes());
-        let c = Scalar::from_hash(h);
-
-        let S0 = S0.decompress().ok_or(DkgError::Decompression)?;
-        let expected_R = EdwardsPoint::mul_base(mu) + S0 * (-c);
-        if *R != expected_R.compress() {
-            return Err(DkgError::ProofOfKnowledge);
+        let S0_point = S0.decompress().ok_or(DkgError::Decompression)?;
+        let R_point = R.decompress().ok_or(DkgError::Decompression)?;
+        check_commitments(Party::Server, &[S0_point, R_point])?;
+        if !verify_knowledge::<CtxDigest>(
+            SERVER_LABEL,
+            Phase::Dkg,
+            S0,
+            &S0_point,
+            R,
+            mu,
+            session_id,
+        ) {
+            // a proof made under our own label points to a role assignment bug rather than a
+            // forged proof
+            if verify_knowledge::<CtxDigest>(
+                CLIENT_LABEL,
+                Phase::Dkg,
+                S0,
+                &S0_point,
+                R,
+                mu,
+                session_id,
+            ) {
+                return Err(DkgError::RoleLabelMismatch {
+                    party: Party::Server,
+                });
+            }
+            return Err(DkgError::ProofOfKnowledge {
+                party: Party::Server,
+            });
         }
 
         Ok(())
     }
 
+    /// The client logic that verifies the server's message in the first round of the distributed
+    /// key generation protocol against the commitment that the server sent ahead of it
+    pub fn finalize_first_round_committed<CtxDigest>(
+        server_commitment: &Round1Commitment,
+        server_message: &DkgServerRound1,
+    ) -> Result<(), DkgError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        Self::finalize_first_round_committed_with_session_id::<CtxDigest>(
+            server_commitment,
+            server_message,
+            &[],
+        )
+    }
+
+    /// `finalize_first_round_committed` for a server message whose proof of knowledge must be
+    /// bound to `session_id`
+    pub fn finalize_first_round_committed_with_session_id<CtxDigest>(
+        server_commitment: &Round1Commitment,
+        server_message: &DkgServerRound1,
+        session_id: &[u8],
+    ) -> Result<(), DkgError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        if server_message.commitment::<CtxDigest>() != *server_commitment {
+            return Err(DkgError::CommitmentMismatch {
+                party: Party::Server,
+            });
+        }
+
+        Self::finalize_first_round_with_session_id::<CtxDigest>(server_message, session_id)
+    }
+
     /// The client logic that starts the second round of the distributed key generation protocol
     pub fn start_second_round(c0: &Scalar, c1: &Scalar) -> (Scalar, DkgClientRound2) {
         let c_client = c0 + c1;
@@ -129,10 +346,19 @@ impl ClientDkg {
         C1: &EdwardsPoint,
         server_message_1: &DkgServerRound1,
         server_message_2: &DkgServerRound2,
-    ) -> Result<(Scalar, EdwardsPoint, EdwardsPoint, EdwardsPoint), DkgError> {
+    ) -> Result<DkgOutput, DkgError> {
+        #[cfg(feature = "fault-injection")]
+        crate::fault::take_dkg_fault(crate::fault::DkgStep::ClientFinalizeSecondRound)?;
+
         // 1. Verify that the server provided the correct share from its randomly generated scalar
-        let S0 = server_message_1.S0.decompress().unwrap();
-        let S1 = server_message_1.S1.decompress().unwrap();
+        let S0 = server_message_1
+            .S0
+            .decompress()
+            .ok_or(DkgError::Decompression)?;
+        let S1 = server_message_1
+            .S1
+            .decompress()
+            .ok_or(DkgError::Decompression)?;
 
         let S_client = S0 + S1;
         let expected_S_client = EdwardsPoint::mul_base(&server_message_2.s_client);
@@ -155,6 +381,25 @@ impl ClientDkg {
         // Create the joint public key
         let P_joint = P_client + P_server;
 
-        Ok((p_client, P_client, P_server, P_joint))
+        Ok(DkgOutput {
+            private_share: p_client,
+            own_public: P_client,
+            peer_public: P_server,
+            joint_public: P_joint,
+        })
+    }
+}
+
+/// The distributed key generation protocol with the digest fixed to SHA-512, as in Ed25519
+impl ClientDkg {
+    /// `start_first_round` with SHA-512
+    pub fn start_first_round_sha512(
+    ) -> (Scalar, Scalar, EdwardsPoint, EdwardsPoint, DkgClientRound1) {
+        Self::start_first_round::<Sha512>()
+    }
+
+    /// `finalize_first_round` with SHA-512
+    pub fn finalize_first_round_sha512(server_message: &DkgServerRound1) -> Result<(), DkgError> {
+        Self::finalize_first_round::<Sha512>(server_message)
     }
 }
@@ -1,14 +1,423 @@
+use std::fmt::{self, Debug, Formatter};
+
+use curve25519_dalek::{
+    digest::{generic_array::typenum::U64, Digest},
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+    traits::IsIdentity,
+};
+use rand::{CryptoRng, RngCore};
+use sha2::Sha256;
 use thiserror::Error;
 
+use crate::{
+    dkg::{client::*, server::*},
+    Party, Redacted,
+};
+
 pub mod client;
+pub mod refresh;
 pub mod server;
 
 #[derive(Error, Clone, Debug, Eq, PartialEq)]
 pub enum DkgError {
     #[error("compressed curve point failed to decompress")]
     Decompression,
-    #[error("proof of knowledge failed to verify")]
-    ProofOfKnowledge,
+    #[error("{party} proof of knowledge failed to verify")]
+    ProofOfKnowledge { party: Party },
+    #[error("{party} proof of knowledge was created under the other party's label")]
+    RoleLabelMismatch { party: Party },
+    #[error("{party} commitment is the identity or not in the prime-order subgroup")]
+    WeakCommitment { party: Party },
+    #[error("{party} round 1 message does not match its commitment")]
+    CommitmentMismatch { party: Party },
     #[error("the share verification failed")]
     ShareVerification,
+    #[error("the public key does not match the key derived by the protocol")]
+    KeyMismatch,
+    #[error("the message is malformed")]
+    MalformedMessage,
+    #[error("a scalar in the message is not canonically encoded")]
+    NonCanonicalScalar,
+}
+
+/// The key shares that a party ends the distributed key generation protocol with
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DkgOutput {
+    /// The party's own private key share
+    pub private_share: Scalar,
+    /// The party's own public key share
+    pub own_public: EdwardsPoint,
+    /// The public key share of the other party
+    pub peer_public: EdwardsPoint,
+    /// The joint public key
+    pub joint_public: EdwardsPoint,
+}
+
+impl Debug for DkgOutput {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("DkgOutput")
+            .field("private_share", &Redacted)
+            .field("own_public", &self.own_public)
+            .field("peer_public", &self.peer_public)
+            .field("joint_public", &self.joint_public)
+            .finish()
+    }
+}
+
+impl DkgOutput {
+    /// Switches the output of `party` to the aggregated key mode, where the joint public key is
+    /// `a_client * P_client + a_server * P_server` rather than the plain sum
+    ///
+    /// The coefficients come from `key_aggregation_coefficients`. Both parties must switch, after
+    /// which each holds its share and public key shares scaled by their coefficients. The signing
+    /// functions take the scaled values unchanged, and a partial signature is checked against the
+    /// scaled public key share of its party.
+    #[allow(non_snake_case)]
+    pub fn with_key_aggregation<CtxDigest>(self, party: Party) -> Self
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        let (P_client, P_server) = match party {
+            Party::Client => (self.own_public, self.peer_public),
+            Party::Server => (self.peer_public, self.own_public),
+        };
+        let (a_client, a_server) =
+            key_aggregation_coefficients::<CtxDigest>(&P_client.compress(), &P_server.compress());
+        let (a_own, a_peer) = match party {
+            Party::Client => (a_client, a_server),
+            Party::Server => (a_server, a_client),
+        };
+
+        let own_public = self.own_public * a_own;
+        let peer_public = self.peer_public * a_peer;
+        Self {
+            private_share: self.private_share * a_own,
+            own_public,
+            peer_public,
+            joint_public: own_public + peer_public,
+        }
+    }
+}
+
+/// Computes the MuSig-style key aggregation coefficients `(a_client, a_server)` of the public key
+/// shares
+///
+/// Each coefficient hashes both public key shares, so neither party can pick its share as a
+/// function of its peer's to control the joint public key, even without the proofs of knowledge.
+#[allow(non_snake_case)]
+pub fn key_aggregation_coefficients<CtxDigest>(
+    P_client: &CompressedEdwardsY,
+    P_server: &CompressedEdwardsY,
+) -> (Scalar, Scalar)
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let coefficient = |party: Party| {
+        let mut h = CtxDigest::new();
+        h.update(b"frost key aggregation");
+        h.update(P_client.as_bytes());
+        h.update(P_server.as_bytes());
+        h.update(party_label(party));
+        Scalar::from_hash(h)
+    };
+
+    (coefficient(Party::Client), coefficient(Party::Server))
+}
+
+/// The domain separation label of the client's proof of knowledge
+pub(crate) const CLIENT_LABEL: &[u8] = b"client";
+
+/// The domain separation label of the server's proof of knowledge
+pub(crate) const SERVER_LABEL: &[u8] = b"server";
+
+/// The protocol phase that a proof of knowledge is created for
+///
+/// The phase is hashed into the challenge of the proof, so a proof created during the distributed
+/// key generation cannot be replayed into a share refresh and vice versa.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    Dkg,
+    Refresh,
+}
+
+impl Phase {
+    fn tag(self) -> &'static [u8] {
+        match self {
+            Phase::Dkg => b"dkg",
+            Phase::Refresh => b"refresh",
+        }
+    }
+}
+
+/// A hash commitment to a party's round 1 message, sent ahead of the message itself
+///
+/// In the optional commit-then-reveal flow, a party only reveals its round 1 message after it has
+/// received its peer's commitment. Neither party can then choose its round 1 message after seeing
+/// its peer's, which would let it bias the joint public key.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Round1Commitment(pub [u8; 32]);
+
+/// Computes the commitment of `party` to the byte encoding of its round 1 message
+pub(crate) fn round1_commitment<CtxDigest>(party: Party, message_bytes: &[u8]) -> Round1Commitment
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let mut h = CtxDigest::new();
+    h.update(b"frost dkg round 1 commitment");
+    h.update(party_label(party));
+    h.update(message_bytes);
+
+    let mut commitment = [0u8; 32];
+    commitment.copy_from_slice(&h.finalize()[..32]);
+    Round1Commitment(commitment)
+}
+
+/// A Schnorr proof of knowledge of the discrete log of a commitment
+///
+/// This is the proof that the round 1 messages carry, detached from the commitment so that it can
+/// be stored and verified separately from it.
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ProofOfKnowledge {
+    pub R: CompressedEdwardsY,
+    pub mu: Scalar,
+}
+
+/// Creates a detached proof of knowledge of `x` over the commitment `X = x * G` on behalf of
+/// `party`
+///
+/// The proof is bound to `phase` and `session_id`, so it only verifies for the same phase and
+/// session. The proofs inside the round 1 messages use `Phase::Dkg` and an empty session ID.
+#[allow(non_snake_case)]
+pub fn create_proof<CtxDigest>(
+    rng: &mut (impl RngCore + CryptoRng),
+    party: Party,
+    phase: Phase,
+    x: &Scalar,
+    X: &CompressedEdwardsY,
+    session_id: &[u8],
+) -> ProofOfKnowledge
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let (R, mu) = prove_knowledge::<CtxDigest>(rng, party_label(party), phase, x, X, session_id);
+    ProofOfKnowledge { R, mu }
+}
+
+/// Verifies a detached proof of knowledge that `party` created over `commitment`
+pub fn verify_proof<CtxDigest>(
+    commitment: &CompressedEdwardsY,
+    proof: &ProofOfKnowledge,
+    party: Party,
+    phase: Phase,
+    session_id: &[u8],
+) -> Result<(), DkgError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let commitment_point = commitment.decompress().ok_or(DkgError::Decompression)?;
+    if !verify_knowledge::<CtxDigest>(
+        party_label(party),
+        phase,
+        commitment,
+        &commitment_point,
+        &proof.R,
+        &proof.mu,
+        session_id,
+    ) {
+        return Err(DkgError::ProofOfKnowledge { party });
+    }
+
+    Ok(())
+}
+
+/// The domain separation label of `party`
+pub(crate) fn party_label(party: Party) -> &'static [u8] {
+    match party {
+        Party::Client => CLIENT_LABEL,
+        Party::Server => SERVER_LABEL,
+    }
+}
+
+/// Checks that the commitments that `party` sent are neither the identity nor have a small-order
+/// component
+///
+/// An honest commitment is a multiple of the base point, so it always passes.
+pub(crate) fn check_commitments(party: Party, points: &[EdwardsPoint]) -> Result<(), DkgError> {
+    if points
+        .iter()
+        .any(|point| point.is_identity() || !point.is_torsion_free())
+    {
+        return Err(DkgError::WeakCommitment { party });
+    }
+
+    Ok(())
+}
+
+/// Creates a Schnorr proof of knowledge of `x` over the commitment `X = x * G`
+#[allow(non_snake_case)]
+pub(crate) fn prove_knowledge<CtxDigest>(
+    rng: &mut (impl RngCore + CryptoRng),
+    label: &[u8],
+    phase: Phase,
+    x: &Scalar,
+    X: &CompressedEdwardsY,
+    session_id: &[u8],
+) -> (CompressedEdwardsY, Scalar)
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let k = Scalar::random(rng);
+    let R = EdwardsPoint::mul_base(&k).compress();
+
+    let c = proof_of_knowledge_challenge::<CtxDigest>(label, phase, X, &R, session_id);
+    let mu = k + x * c;
+
+    (R, mu)
+}
+
+/// Verifies a Schnorr proof of knowledge `(R, mu)` over the commitment `X`
+///
+/// The commitment is passed both as it was received on the wire, which is what gets hashed, and
+/// in its decompressed form.
+#[allow(non_snake_case)]
+pub(crate) fn verify_knowledge<CtxDigest>(
+    label: &[u8],
+    phase: Phase,
+    X: &CompressedEdwardsY,
+    X_point: &EdwardsPoint,
+    R: &CompressedEdwardsY,
+    mu: &Scalar,
+    session_id: &[u8],
+) -> bool
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let c = proof_of_knowledge_challenge::<CtxDigest>(label, phase, X, R, session_id);
+    let expected_R = EdwardsPoint::mul_base(mu) + X_point * (-c);
+
+    *R == expected_R.compress()
+}
+
+#[allow(non_snake_case)]
+fn proof_of_knowledge_challenge<CtxDigest>(
+    label: &[u8],
+    phase: Phase,
+    X: &CompressedEdwardsY,
+    R: &CompressedEdwardsY,
+    session_id: &[u8],
+) -> Scalar
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let mut h = CtxDigest::new();
+    h.update(label);
+    h.update(phase.tag());
+    h.update(X.as_bytes());
+    h.update(R.as_bytes());
+    h.update(session_id);
+    Scalar::from_hash(h)
+}
+
+/// Verifies the round 1 messages of both parties of the distributed key generation protocol
+///
+/// This is a convenience for settings where a single process holds the messages of both parties.
+/// The client's message is checked first, so if both proofs of knowledge are invalid, the error is
+/// attributed to the client.
+pub fn verify_round1_mutual<CtxDigest>(
+    client_message: &DkgClientRound1,
+    server_message: &DkgServerRound1,
+) -> Result<(), DkgError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    ServerDkg::finalize_first_round::<CtxDigest>(client_message)?;
+    ClientDkg::finalize_first_round::<CtxDigest>(server_message)
+}
+
+/// Verifies that the public key advertised by the holder of the client's secret share, such as an
+/// HSM, is the client's public key share `P_client` derived by the protocol
+#[allow(non_snake_case)]
+pub fn verify_client_public_against(
+    P_client: &EdwardsPoint,
+    hsm_public: &CompressedEdwardsY,
+) -> Result<(), DkgError> {
+    if P_client.compress() != *hsm_public {
+        return Err(DkgError::KeyMismatch);
+    }
+
+    Ok(())
+}
+
+/// Verifies that a secret reconstructed from the key shares, such as `p_client + p_server`, is the
+/// secret key of the joint public key
+#[allow(non_snake_case)]
+pub fn verify_reconstructed_secret(
+    secret: &Scalar,
+    P_joint: &CompressedEdwardsY,
+) -> Result<(), DkgError> {
+    if EdwardsPoint::mul_base(secret).compress() != *P_joint {
+        return Err(DkgError::KeyMismatch);
+    }
+
+    Ok(())
+}
+
+/// Computes a short fingerprint of the joint public key for logs and key management
+///
+/// The fingerprint is the first 8 bytes of `SHA-256(P_joint)` as colon-separated hex, such as
+/// `21:fe:31:df:a1:54:a2:61`. It identifies a key for humans but is too short to authenticate one.
+#[allow(non_snake_case)]
+pub fn joint_key_fingerprint(P_joint: &CompressedEdwardsY) -> String {
+    Sha256::digest(P_joint.as_bytes())[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// The public key shares that a joint public key was aggregated from
+///
+/// Anyone can add the two shares, so the proof reveals nothing beyond the shares themselves. It
+/// packages them for registries that publish joint keys together with their shares.
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AggregationProof {
+    pub P_client: CompressedEdwardsY,
+    pub P_server: CompressedEdwardsY,
+}
+
+/// Packages the public key shares into a proof that they sum to the joint public key
+#[allow(non_snake_case)]
+pub fn prove_aggregation(
+    P_client: &CompressedEdwardsY,
+    P_server: &CompressedEdwardsY,
+) -> AggregationProof {
+    AggregationProof {
+        P_client: *P_client,
+        P_server: *P_server,
+    }
+}
+
+/// Verifies that the public key shares of `proof` are valid points that sum to `P_joint`
+///
+/// A share that is the identity or has a torsion component fails with
+/// `DkgError::WeakCommitment`, as in the first round of the DKG. Without this check, the shares
+/// `P_client + T` and `P_server - T` for a small-order point `T` would also sum to `P_joint`.
+#[allow(non_snake_case)]
+pub fn verify_aggregation(
+    P_joint: &CompressedEdwardsY,
+    proof: &AggregationProof,
+) -> Result<(), DkgError> {
+    let P_client = proof.P_client.decompress().ok_or(DkgError::Decompression)?;
+    let P_server = proof.P_server.decompress().ok_or(DkgError::Decompression)?;
+    check_commitments(Party::Client, &[P_client])?;
+    check_commitments(Party::Server, &[P_server])?;
+
+    if (P_client + P_server).compress() != *P_joint {
+        return Err(DkgError::KeyMismatch);
+    }
+
+    Ok(())
 }
@@ -0,0 +1,96 @@
+//! Proactive refresh of the key shares
+//!
+//! Each party draws a random scalar `delta` and sends it to the other party over the same private
+//! channel as the second round of the distributed key generation protocol. A party subtracts its
+//! own `delta` from its key share and adds its peer's, so the shares change while their sum, and
+//! with it the joint public key, stays the same. A share compromised before a refresh is of no use
+//! together with a share from after it.
+
+use std::fmt::{self, Debug, Formatter};
+
+use curve25519_dalek::{edwards::EdwardsPoint, scalar::Scalar};
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+
+use crate::{
+    dkg::{DkgError, DkgOutput},
+    key_share::KeyShare,
+    wire::scalar_at,
+    Redacted,
+};
+
+/// The message that each party sends to the other party in the refresh protocol
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RefreshMessage {
+    pub delta: Scalar,
+}
+
+impl Debug for RefreshMessage {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("RefreshMessage")
+            .field("delta", &Redacted)
+            .finish()
+    }
+}
+
+impl RefreshMessage {
+    /// The length of the byte encoding of the message
+    pub const LENGTH: usize = 32;
+
+    /// Encodes the message as `delta`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.delta.as_bytes().to_vec()
+    }
+
+    /// Decodes a message encoded by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(DkgError::MalformedMessage);
+        }
+
+        Ok(Self {
+            delta: scalar_at(bytes, 0).ok_or(DkgError::NonCanonicalScalar)?,
+        })
+    }
+}
+
+/// Starts the refresh protocol, which runs the same way for the client and the server
+pub fn start_refresh() -> (Scalar, RefreshMessage) {
+    start_refresh_with_rng(&mut OsRng)
+}
+
+/// Starts the refresh protocol, drawing its randomness from `rng`
+pub fn start_refresh_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> (Scalar, RefreshMessage) {
+    let delta = Scalar::random(rng);
+    (delta, RefreshMessage { delta })
+}
+
+/// Finishes the refresh protocol and returns the refreshed key share
+///
+/// The peer's `delta` cannot be validated here. Whatever its value, the refreshed public key
+/// shares sum to the same point as the old ones, so a peer that applies a different `delta` to its
+/// own share is only caught when the partial signatures stop verifying. The sum is still compared
+/// with the joint public key of `key_share`, which fails with `DkgError::KeyMismatch` if
+/// `key_share` was already inconsistent, with its private share not matching its public keys.
+#[allow(non_snake_case)]
+pub fn finalize_refresh(
+    key_share: &KeyShare,
+    own_delta: &Scalar,
+    peer_message: &RefreshMessage,
+) -> Result<KeyShare, DkgError> {
+    let private_share = key_share.private_share() - own_delta + peer_message.delta;
+
+    let own_public = EdwardsPoint::mul_base(&private_share);
+    let peer_public = key_share.peer_public() + EdwardsPoint::mul_base(own_delta)
+        - EdwardsPoint::mul_base(&peer_message.delta);
+
+    if own_public + peer_public != *key_share.joint_public() {
+        return Err(DkgError::KeyMismatch);
+    }
+
+    Ok(KeyShare::new(DkgOutput {
+        private_share,
+        own_public,
+        peer_public,
+        joint_public: *key_share.joint_public(),
+    }))
+}
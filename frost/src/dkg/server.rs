@@ -1,4 +1,8 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Display, Formatter},
+    str::FromStr,
+};
 
 use base64::{prelude::BASE64_STANDARD, Engine};
 use curve25519_dalek::{
@@ -6,17 +10,22 @@ use curve25519_dalek::{
     edwards::{CompressedEdwardsY, EdwardsPoint},
     scalar::Scalar,
 };
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use sha2::Sha512;
 
 use crate::dkg::{
+    check_commitments,
     client::{DkgClientRound1, DkgClientRound2},
-    DkgError,
+    prove_knowledge, round1_commitment, verify_knowledge, DkgError, DkgOutput, Phase,
+    ProofOfKnowledge, Round1Commitment, CLIENT_LABEL, SERVER_LABEL,
 };
+use crate::wire::{base64_segments, point_at, scalar_at};
+use crate::{Party, Redacted};
 
 /// The message that the server sends over to the client at round 1 of the distributed key
 /// generation protocol
 #[allow(non_snake_case)]
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct DkgServerRound1 {
     pub S0: CompressedEdwardsY,
     pub S1: CompressedEdwardsY,
@@ -24,6 +33,16 @@ pub struct DkgServerRound1 {
     pub mu: Scalar,
 }
 
+impl DkgServerRound1 {
+    /// The proof of knowledge over `S0`, detached from the commitments
+    pub fn proof_of_knowledge(&self) -> ProofOfKnowledge {
+        ProofOfKnowledge {
+            R: self.R,
+            mu: self.mu,
+        }
+    }
+}
+
 impl Display for DkgServerRound1 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{}", BASE64_STANDARD.encode(self.S0.as_bytes()))?;
@@ -33,20 +52,122 @@ impl Display for DkgServerRound1 {
     }
 }
 
+impl FromStr for DkgServerRound1 {
+    type Err = DkgError;
+
+    /// Decodes a message from the base64 form written by its `Display` impl
+    fn from_str(s: &str) -> Result<Self, DkgError> {
+        let bytes = base64_segments(s, &[32; 4]).ok_or(DkgError::MalformedMessage)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Orders the messages by their byte encodings, so that they can be used as keys of a `BTreeMap`
+impl Ord for DkgServerRound1 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_bytes().cmp(&other.to_bytes())
+    }
+}
+
+impl PartialOrd for DkgServerRound1 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl DkgServerRound1 {
+    /// The length of the byte encoding of the message
+    pub const LENGTH: usize = 128;
+
+    /// Encodes the message as `S0 || S1 || R || mu`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::LENGTH);
+        bytes.extend_from_slice(self.S0.as_bytes());
+        bytes.extend_from_slice(self.S1.as_bytes());
+        bytes.extend_from_slice(self.R.as_bytes());
+        bytes.extend_from_slice(self.mu.as_bytes());
+        bytes
+    }
+
+    /// The commitment to the message for the commit-then-reveal flow
+    pub fn commitment<CtxDigest>(&self) -> Round1Commitment
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        round1_commitment::<CtxDigest>(Party::Server, &self.to_bytes())
+    }
+
+    /// Decodes a message encoded by `to_bytes`
+    ///
+    /// The points are not decompressed here, so an invalid point is only caught by the round that
+    /// uses it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(DkgError::MalformedMessage);
+        }
+
+        Ok(Self {
+            S0: point_at(bytes, 0),
+            S1: point_at(bytes, 32),
+            R: point_at(bytes, 64),
+            mu: scalar_at(bytes, 96).ok_or(DkgError::NonCanonicalScalar)?,
+        })
+    }
+}
+
 /// The message that the server sends over to the client at round 2 of the distributed key
 /// generation protocol
 #[allow(non_snake_case)]
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct DkgServerRound2 {
     pub s_client: Scalar,
 }
 
+impl Debug for DkgServerRound2 {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("DkgServerRound2")
+            .field("s_client", &Redacted)
+            .finish()
+    }
+}
+
 impl Display for DkgServerRound2 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{}", BASE64_STANDARD.encode(self.s_client.as_bytes()))
     }
 }
 
+impl FromStr for DkgServerRound2 {
+    type Err = DkgError;
+
+    /// Decodes a message from the base64 form written by its `Display` impl
+    fn from_str(s: &str) -> Result<Self, DkgError> {
+        let bytes = base64_segments(s, &[32]).ok_or(DkgError::MalformedMessage)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl DkgServerRound2 {
+    /// The length of the byte encoding of the message
+    pub const LENGTH: usize = 32;
+
+    /// Encodes the message as `s_client`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.s_client.as_bytes().to_vec()
+    }
+
+    /// Decodes a message encoded by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(DkgError::MalformedMessage);
+        }
+
+        Ok(Self {
+            s_client: scalar_at(bytes, 0).ok_or(DkgError::NonCanonicalScalar)?,
+        })
+    }
+}
+
 pub struct ServerDkg;
 #[allow(non_snake_case)]
 impl ServerDkg {
@@ -55,33 +176,59 @@ impl ServerDkg {
     /// It does not matter whether the client or the server starts the protocol first.
     pub fn start_first_round<CtxDigest>(
     ) -> (Scalar, Scalar, EdwardsPoint, EdwardsPoint, DkgServerRound1)
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        Self::start_first_round_with_rng::<CtxDigest>(&mut OsRng)
+    }
+
+    /// The server logic that starts the first round of the distributed key generation protocol,
+    /// drawing its randomness from `rng`
+    pub fn start_first_round_with_rng<CtxDigest>(
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (Scalar, Scalar, EdwardsPoint, EdwardsPoint, DkgServerRound1)
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        Self::start_first_round_with_session_id::<CtxDigest>(rng, &[])
+    }
+
+    /// The server logic that starts the first round of the distributed key generation protocol,
+    /// binding the server's proof of knowledge to `session_id`
+    ///
+    /// `session_id` identifies this run of the protocol, such as a value that both parties agreed
+    /// on beforehand. The client must verify the proof with `finalize_first_round_with_session_id`
+    /// and the same `session_id`, so a round 1 message cannot be replayed into another session.
+    pub fn start_first_round_with_session_id<CtxDigest>(
+        rng: &mut (impl RngCore + CryptoRng),
+        session_id: &[u8],
+    ) -> (Scalar, Scalar, EdwardsPoint, EdwardsPoint, DkgServerRound1)
     where
         CtxDigest: Digest<OutputSize = U64>,
     {
         // 1. Generates two random scalar elements
-        let s0 = Scalar::random(&mut OsRng);
-        let s1 = Scalar::random(&mut OsRng);
+        let s0 = Scalar::random(rng);
+        let s1 = Scalar::random(rng);
 
         // 2. Commits to the two scalar elements above as elliptic curve points
         let S0 = EdwardsPoint::mul_base(&s0);
         let S1 = EdwardsPoint::mul_base(&s1);
 
         // 3. Create a proof of knowledge of `s0` over `S0`
-        let k = Scalar::random(&mut OsRng);
-        let R = EdwardsPoint::mul_base(&k);
-
-        let mut h = CtxDigest::new();
-        h.update(b"server");
-        h.update(S0.compress().as_bytes());
-        h.update(R.compress().as_bytes());
-        let c = Scalar::from_hash(h);
-        let mu = k + s0 * c;
+        let (R, mu) = prove_knowledge::<CtxDigest>(
+            rng,
+            SERVER_LABEL,
+            Phase::Dkg,
+            &s0,
+            &S0.compress(),
+            session_id,
+        );
 
         // 4. Construct the server's message to the client
         let server_message = DkgServerRound1 {
             S0: S0.compress(),
             S1: S1.compress(),
-            R: R.compress(),
+            R,
             mu,
         };
 
@@ -94,24 +241,94 @@ impl ServerDkg {
     where
         CtxDigest: Digest<OutputSize = U64>,
     {
+        Self::finalize_first_round_with_session_id::<CtxDigest>(client_message, &[])
+    }
+
+    /// The server logic that verifies the client's message in the first round of the distributed
+    /// key generation protocol, whose proof of knowledge must be bound to `session_id`
+    pub fn finalize_first_round_with_session_id<CtxDigest>(
+        client_message: &DkgClientRound1,
+        session_id: &[u8],
+    ) -> Result<(), DkgError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        #[cfg(feature = "fault-injection")]
+        crate::fault::take_dkg_fault(crate::fault::DkgStep::ServerFinalizeFirstRound)?;
+
         let DkgClientRound1 { C0, C1: _, R, mu } = client_message;
 
         // verify the client's proof of knowledge
-        let mut h = CtxDigest::new();
-        h.update(b"client");
-        h.update(C0.as_bytes());
-        h.update(R.as_bytes());
-        let c = Scalar::from_hash(h);
-
-        let C0 = C0.decompress().unwrap();
-        let expected_R = EdwardsPoint::mul_base(mu) + C0 * (-c);
-        if *R != expected_R.compress() {
-            return Err(DkgError::ProofOfKnowledge);
+        let C0_point = C0.decompress().ok_or(DkgError::Decompression)?;
+        let R_point = R.decompress().ok_or(DkgError::Decompression)?;
+        check_commitments(Party::Client, &[C0_point, R_point])?;
+        if !verify_knowledge::<CtxDigest>(
+            CLIENT_LABEL,
+            Phase::Dkg,
+            C0,
+            &C0_point,
+            R,
+            mu,
+            session_id,
+        ) {
+            // a proof made under our own label points to a role assignment bug rather than a
+            // forged proof
+            if verify_knowledge::<CtxDigest>(
+                SERVER_LABEL,
+                Phase::Dkg,
+                C0,
+                &C0_point,
+                R,
+                mu,
+                session_id,
+            ) {
+                return Err(DkgError::RoleLabelMismatch {
+                    party: Party::Client,
+                });
+            }
+            return Err(DkgError::ProofOfKnowledge {
+                party: Party::Client,
+            });
         }
 
         Ok(())
     }
 
+    /// The server logic that verifies the client's message in the first round of the distributed
+    /// key generation protocol against the commitment that the client sent ahead of it
+    pub fn finalize_first_round_committed<CtxDigest>(
+        client_commitment: &Round1Commitment,
+        client_message: &DkgClientRound1,
+    ) -> Result<(), DkgError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        Self::finalize_first_round_committed_with_session_id::<CtxDigest>(
+            client_commitment,
+            client_message,
+            &[],
+        )
+    }
+
+    /// `finalize_first_round_committed` for a client message whose proof of knowledge must be
+    /// bound to `session_id`
+    pub fn finalize_first_round_committed_with_session_id<CtxDigest>(
+        client_commitment: &Round1Commitment,
+        client_message: &DkgClientRound1,
+        session_id: &[u8],
+    ) -> Result<(), DkgError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        if client_message.commitment::<CtxDigest>() != *client_commitment {
+            return Err(DkgError::CommitmentMismatch {
+                party: Party::Client,
+            });
+        }
+
+        Self::finalize_first_round_with_session_id::<CtxDigest>(client_message, session_id)
+    }
+
     /// The server logic that starts the second round of the distributed key generation protocol
     pub fn start_second_round(s0: &Scalar, s1: &Scalar) -> (Scalar, DkgServerRound2) {
         let s_client = s0 + s1;
@@ -129,10 +346,19 @@ impl ServerDkg {
         S1: &EdwardsPoint,
         client_message_1: &DkgClientRound1,
         client_message_2: &DkgClientRound2,
-    ) -> Result<(Scalar, EdwardsPoint, EdwardsPoint, EdwardsPoint), DkgError> {
+    ) -> Result<DkgOutput, DkgError> {
+        #[cfg(feature = "fault-injection")]
+        crate::fault::take_dkg_fault(crate::fault::DkgStep::ServerFinalizeSecondRound)?;
+
         // 1. Verify that the client provided the correct share from its randomly generated scalar
-        let C0 = client_message_1.C0.decompress().unwrap();
-        let C1 = client_message_1.C1.decompress().unwrap();
+        let C0 = client_message_1
+            .C0
+            .decompress()
+            .ok_or(DkgError::Decompression)?;
+        let C1 = client_message_1
+            .C1
+            .decompress()
+            .ok_or(DkgError::Decompression)?;
 
         let C_server = C0 - C1;
         let expected_C_server = EdwardsPoint::mul_base(&client_message_2.c_server);
@@ -155,6 +381,25 @@ impl ServerDkg {
         // Create the joint public key
         let P_joint = P_client + P_server;
 
-        Ok((p_server, P_server, P_client, P_joint))
+        Ok(DkgOutput {
+            private_share: p_server,
+            own_public: P_server,
+            peer_public: P_client,
+            joint_public: P_joint,
+        })
+    }
+}
+
+/// The distributed key generation protocol with the digest fixed to SHA-512, as in Ed25519
+impl ServerDkg {
+    /// `start_first_round` with SHA-512
+    pub fn start_first_round_sha512(
+    ) -> (Scalar, Scalar, EdwardsPoint, EdwardsPoint, DkgServerRound1) {
+        Self::start_first_round::<Sha512>()
+    }
+
+    /// `finalize_first_round` with SHA-512
+    pub fn finalize_first_round_sha512(client_message: &DkgClientRound1) -> Result<(), DkgError> {
+        Self::finalize_first_round::<Sha512>(client_message)
     }
 }
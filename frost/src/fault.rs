@@ -0,0 +1,67 @@
+//! Hooks that force protocol steps to return an error, for exercising error handling
+//!
+//! Faults are armed per thread and each one is consumed by the first call to the step it targets.
+//! This module is only compiled with the `fault-injection` feature.
+
+use std::cell::RefCell;
+
+use crate::{dkg::DkgError, sign::SignError};
+
+/// A step of the distributed key generation protocol that a fault can be injected into
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DkgStep {
+    ClientFinalizeFirstRound,
+    ServerFinalizeFirstRound,
+    ClientFinalizeSecondRound,
+    ServerFinalizeSecondRound,
+}
+
+/// A step of the distributed signing protocol that a fault can be injected into
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SignStep {
+    ClientSecondRound,
+    ServerSecondRound,
+    ClientCombineSigs,
+    ServerCombineSigs,
+}
+
+thread_local! {
+    static DKG_FAULTS: RefCell<Vec<(DkgStep, DkgError)>> = const { RefCell::new(Vec::new()) };
+    static SIGN_FAULTS: RefCell<Vec<(SignStep, SignError)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Makes the next call to `step` on this thread return `error`
+pub fn inject_dkg_fault(step: DkgStep, error: DkgError) {
+    DKG_FAULTS.with(|faults| faults.borrow_mut().push((step, error)));
+}
+
+/// Makes the next call to `step` on this thread return `error`
+pub fn inject_sign_fault(step: SignStep, error: SignError) {
+    SIGN_FAULTS.with(|faults| faults.borrow_mut().push((step, error)));
+}
+
+/// Disarms all faults that have not been triggered yet on this thread
+pub fn clear_faults() {
+    DKG_FAULTS.with(|faults| faults.borrow_mut().clear());
+    SIGN_FAULTS.with(|faults| faults.borrow_mut().clear());
+}
+
+pub(crate) fn take_dkg_fault(step: DkgStep) -> Result<(), DkgError> {
+    DKG_FAULTS.with(|faults| {
+        let mut faults = faults.borrow_mut();
+        match faults.iter().position(|(s, _)| *s == step) {
+            Some(i) => Err(faults.remove(i).1),
+            None => Ok(()),
+        }
+    })
+}
+
+pub(crate) fn take_sign_fault(step: SignStep) -> Result<(), SignError> {
+    SIGN_FAULTS.with(|faults| {
+        let mut faults = faults.borrow_mut();
+        match faults.iter().position(|(s, _)| *s == step) {
+            Some(i) => Err(faults.remove(i).1),
+            None => Ok(()),
+        }
+    })
+}
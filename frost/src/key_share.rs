@@ -0,0 +1,151 @@
+//! A party's key share as it comes out of the distributed key generation protocol
+//!
+//! The share bundles the party's private key share with the public key shares of both parties and
+//! the joint public key, so that the signing functions can take it in place of the loose values.
+//! It can be encoded with `to_bytes` for storage and reloaded with `from_bytes`.
+
+use std::fmt::{self, Debug, Formatter};
+
+use curve25519_dalek::{edwards::EdwardsPoint, scalar::Scalar};
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    dkg::DkgOutput,
+    wire::{point_at, scalar_at},
+    Redacted,
+};
+
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum KeyShareError {
+    #[error("the encoding of the key share is malformed")]
+    Malformed,
+    #[error("the private key share is not canonically encoded")]
+    NonCanonicalScalar,
+    #[error("the key share encoding version {0} is not supported")]
+    UnsupportedVersion(u8),
+    #[error("compressed curve point failed to decompress")]
+    Decompression,
+    #[error("the public keys do not match the private key share")]
+    KeyMismatch,
+}
+
+/// A party's key share, whose private key share is zeroized when dropped
+#[derive(Clone, PartialEq, Eq)]
+pub struct KeyShare {
+    private_share: Scalar,
+    own_public: EdwardsPoint,
+    peer_public: EdwardsPoint,
+    joint_public: EdwardsPoint,
+}
+
+impl KeyShare {
+    /// Creates the key share from the output of the distributed key generation protocol
+    pub fn new(output: DkgOutput) -> Self {
+        Self {
+            private_share: output.private_share,
+            own_public: output.own_public,
+            peer_public: output.peer_public,
+            joint_public: output.joint_public,
+        }
+    }
+
+    /// The party's own private key share
+    pub fn private_share(&self) -> &Scalar {
+        &self.private_share
+    }
+
+    /// The party's own public key share
+    pub fn own_public(&self) -> &EdwardsPoint {
+        &self.own_public
+    }
+
+    /// The public key share of the other party
+    pub fn peer_public(&self) -> &EdwardsPoint {
+        &self.peer_public
+    }
+
+    /// The joint public key
+    pub fn joint_public(&self) -> &EdwardsPoint {
+        &self.joint_public
+    }
+}
+
+impl KeyShare {
+    /// The version of the byte encoding of the key share
+    pub const VERSION: u8 = 1;
+
+    /// The length of the byte encoding of the key share
+    pub const LENGTH: usize = 129;
+
+    /// Encodes the key share as `version || private_share || own_public || peer_public ||
+    /// joint_public`
+    ///
+    /// The encoding contains the private key share, so it is zeroized when dropped.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(Self::LENGTH));
+        bytes.push(Self::VERSION);
+        bytes.extend_from_slice(self.private_share.as_bytes());
+        bytes.extend_from_slice(self.own_public.compress().as_bytes());
+        bytes.extend_from_slice(self.peer_public.compress().as_bytes());
+        bytes.extend_from_slice(self.joint_public.compress().as_bytes());
+        bytes
+    }
+
+    /// Decodes a key share encoded by `to_bytes`
+    ///
+    /// The public keys are checked against the private key share and against each other, so that
+    /// a key share corrupted in storage is rejected on load.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KeyShareError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(KeyShareError::Malformed);
+        }
+        if bytes[0] != Self::VERSION {
+            return Err(KeyShareError::UnsupportedVersion(bytes[0]));
+        }
+
+        let key_share = Self {
+            private_share: scalar_at(bytes, 1).ok_or(KeyShareError::NonCanonicalScalar)?,
+            own_public: decompress_at(bytes, 33)?,
+            peer_public: decompress_at(bytes, 65)?,
+            joint_public: decompress_at(bytes, 97)?,
+        };
+
+        if EdwardsPoint::mul_base(&key_share.private_share) != key_share.own_public
+            || key_share.own_public + key_share.peer_public != key_share.joint_public
+        {
+            return Err(KeyShareError::KeyMismatch);
+        }
+
+        Ok(key_share)
+    }
+}
+
+fn decompress_at(bytes: &[u8], offset: usize) -> Result<EdwardsPoint, KeyShareError> {
+    point_at(bytes, offset)
+        .decompress()
+        .ok_or(KeyShareError::Decompression)
+}
+
+impl From<DkgOutput> for KeyShare {
+    fn from(output: DkgOutput) -> Self {
+        Self::new(output)
+    }
+}
+
+impl Debug for KeyShare {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("private_share", &Redacted)
+            .field("own_public", &self.own_public.compress())
+            .field("peer_public", &self.peer_public.compress())
+            .field("joint_public", &self.joint_public.compress())
+            .finish()
+    }
+}
+
+impl Drop for KeyShare {
+    fn drop(&mut self) {
+        self.private_share.zeroize();
+    }
+}
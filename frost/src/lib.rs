@@ -1,12 +1,128 @@
+use std::fmt::{self, Debug, Display, Formatter};
+
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use thiserror::Error;
+
+use crate::{dkg::DkgError, key_share::KeyShareError, sign::SignError};
+
+#[cfg(feature = "base58")]
+pub mod address;
 pub mod dkg;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub mod key_share;
+#[cfg(feature = "dangerous-recovery")]
+pub mod recovery;
 pub mod sign;
+pub mod store;
+#[cfg(feature = "async")]
+pub mod transport;
+pub mod vectors;
+mod wire;
+
+/// The two participants of the protocol
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Party {
+    Client,
+    Server,
+}
+
+impl Display for Party {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Party::Client => write!(f, "client"),
+            Party::Server => write!(f, "server"),
+        }
+    }
+}
+
+/// Stands in for a secret value in `Debug` output
+pub(crate) struct Redacted;
+
+impl Debug for Redacted {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+/// An error of any stage of the protocol, for callers that chain the key generation into signing
+///
+/// The stage-specific errors convert into it with `?`, and remain available for callers that want
+/// to match on them directly.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum FrostError {
+    #[error(transparent)]
+    Dkg(#[from] DkgError),
+    #[error(transparent)]
+    Sign(#[from] SignError),
+    #[error(transparent)]
+    KeyShare(#[from] KeyShareError),
+}
+
+/// The random number generators of a reproducible protocol session
+///
+/// The randomness of both parties is derived from a shared seed with ChaCha20, each party on its
+/// own stream. Passing the generators to the `*_with_rng` round functions makes the messages and the
+/// final signature byte-identical across runs with the same seed. This is meant for tests only, as
+/// anyone who knows the seed knows every secret of the session.
+pub struct DeterministicSession {
+    pub client_rng: ChaCha20Rng,
+    pub server_rng: ChaCha20Rng,
+}
+
+/// Creates the random number generators of a reproducible protocol session from `seed`
+pub fn deterministic_session(seed: [u8; 32]) -> DeterministicSession {
+    let mut client_rng = ChaCha20Rng::from_seed(seed);
+    client_rng.set_stream(0);
+
+    let mut server_rng = ChaCha20Rng::from_seed(seed);
+    server_rng.set_stream(1);
+
+    DeterministicSession {
+        client_rng,
+        server_rng,
+    }
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::dkg::{client::*, server::*};
-    use crate::sign::{client::*, server::*};
-    use curve25519_dalek::EdwardsPoint;
+    use crate::dkg::{
+        client::*,
+        create_proof, joint_key_fingerprint, key_aggregation_coefficients, prove_aggregation,
+        prove_knowledge,
+        refresh::{finalize_refresh, start_refresh, RefreshMessage},
+        server::*,
+        verify_aggregation, verify_client_public_against, verify_proof,
+        verify_reconstructed_secret, verify_round1_mutual, DkgError, DkgOutput, Phase,
+        SERVER_LABEL,
+    };
+    use crate::key_share::{KeyShare, KeyShareError};
+    use crate::sign::{
+        binding_factor, check_timestamp,
+        client::*,
+        coordinator::{aggregate_partials, verify_partial, PartialSignatureMessage},
+        derive_session_id, from_signature_bytes, joint_key_to_montgomery,
+        key_image::{combine_key_image, partial_key_image},
+        prehash, prehash_reader, prehash_reader_with_limit, r_from_y_and_parity, r_y_and_parity,
+        receipt::{issue_receipt, verify_receipt},
+        server::*,
+        signing_challenge, to_signature_bytes, two_party_sign, verify_external_signature,
+        verify_partial_with_R, verify_signature, verify_signature_sha512,
+        verify_signature_with_variant, verify_streaming, verify_streaming_with_limit,
+        verify_verbose, PreparedCombiner, SignError, SigningVariant,
+    };
+    use crate::store::{MemorySecretStore, SecretStore};
+    use crate::vectors::conformance_vectors;
+    use crate::{deterministic_session, DeterministicSession, FrostError, Party};
+    use base64::{prelude::BASE64_STANDARD, Engine};
+    use curve25519_dalek::{
+        constants::EIGHT_TORSION, edwards::CompressedEdwardsY, montgomery::MontgomeryPoint,
+        traits::Identity, EdwardsPoint, Scalar,
+    };
+    use rand::rngs::OsRng;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
     use sha2::Sha512;
+    use zeroize::Zeroize;
 
     #[allow(non_snake_case)]
     #[test]
@@ -21,7 +137,12 @@ mod tests {
         let (c_client, client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
         let (s_server, server_dkg_message_2) = ServerDkg::start_second_round(&s0, &s1);
 
-        let (p_client, P_client_1, P_server_1, P_joint_1) = ClientDkg::finalize_second_round(
+        let DkgOutput {
+            private_share: p_client,
+            own_public: P_client_1,
+            peer_public: P_server_1,
+            joint_public: P_joint_1,
+        } = ClientDkg::finalize_second_round(
             &c_client,
             &C0,
             &C1,
@@ -30,7 +151,12 @@ mod tests {
         )
         .unwrap();
 
-        let (p_server, P_server_2, P_client_2, P_joint_2) = ServerDkg::finalize_second_round(
+        let DkgOutput {
+            private_share: p_server,
+            own_public: P_server_2,
+            peer_public: P_client_2,
+            joint_public: P_joint_2,
+        } = ServerDkg::finalize_second_round(
             &s_server,
             &S0,
             &S1,
@@ -117,5 +243,3722 @@ mod tests {
         assert_eq!(R_1.compress(), R_1_post);
         assert_eq!(R_1_post, R_2_post);
         assert_eq!(z_1, z_2);
+
+        // make sure that the final signature verifies under the joint public key
+        verify_signature::<Sha512>(&P_joint, message, &R_1_post, &z_1).unwrap();
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_display_does_not_leak_secrets() {
+        // The scalars that are sent over the wire are public by design:
+        //   - `mu` in the round 1 DKG messages (proof of knowledge response)
+        //   - `c_server` and `s_client` in the round 2 DKG messages (the peer's share)
+        //   - `z_client` and `z_server` in the round 2 signing messages (partial signatures)
+        //
+        // The scalars that each party keeps to itself are secret and must never be printed:
+        //   - `c0`, `c1`, `s0`, `s1` and `c_client`, `s_server` during DKG
+        //   - the private key shares `p_client` and `p_server`
+        //   - the signing nonces `d_client`, `e_client`, `d_server`, `e_server`
+        let (c0, c1, C0, C1, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let (s0, s1, S0, S1, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+        let (c_client, client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
+        let (s_server, server_dkg_message_2) = ServerDkg::start_second_round(&s0, &s1);
+
+        let DkgOutput {
+            private_share: p_client,
+            peer_public: P_server,
+            joint_public: P_joint,
+            ..
+        } = ClientDkg::finalize_second_round(
+            &c_client,
+            &C0,
+            &C1,
+            &server_dkg_message_1,
+            &server_dkg_message_2,
+        )
+        .unwrap();
+        let DkgOutput {
+            private_share: p_server,
+            ..
+        } = ServerDkg::finalize_second_round(
+            &s_server,
+            &S0,
+            &S1,
+            &client_dkg_message_1,
+            &client_dkg_message_2,
+        )
+        .unwrap();
+
+        let P_joint = P_joint.compress();
+        let P_server = P_server.compress();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+        let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round::<Sha512>(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        ClientSign::combine_sigs::<Sha512>(
+            &P_joint,
+            &P_server,
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+
+        let secrets = [
+            c0, c1, s0, s1, c_client, s_server, p_client, p_server, d_client, e_client, d_server,
+            e_server,
+        ];
+        let displayed = [
+            client_dkg_message_1.to_string(),
+            server_dkg_message_1.to_string(),
+            client_dkg_message_2.to_string(),
+            server_dkg_message_2.to_string(),
+            client_sign_message_1.to_string(),
+            server_sign_message_1.to_string(),
+            client_sign_message_2.to_string(),
+            server_sign_message_2.to_string(),
+        ];
+
+        for secret in secrets {
+            let encoded = BASE64_STANDARD.encode(secret.as_bytes());
+            for output in &displayed {
+                assert!(!output.contains(&encoded));
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_verify_round1_mutual() {
+        let (_, _, _, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let (_, _, _, _, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+
+        verify_round1_mutual::<Sha512>(&client_dkg_message_1, &server_dkg_message_1).unwrap();
+
+        let mut bad_client_message = client_dkg_message_1;
+        bad_client_message.mu += Scalar::ONE;
+        assert_eq!(
+            verify_round1_mutual::<Sha512>(&bad_client_message, &server_dkg_message_1),
+            Err(DkgError::ProofOfKnowledge {
+                party: Party::Client
+            }),
+        );
+
+        let mut bad_server_message = server_dkg_message_1;
+        bad_server_message.mu += Scalar::ONE;
+        assert_eq!(
+            verify_round1_mutual::<Sha512>(&client_dkg_message_1, &bad_server_message),
+            Err(DkgError::ProofOfKnowledge {
+                party: Party::Server
+            }),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_role_label_mismatch() {
+        let (c0, _, C0, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+
+        // the client mistakenly creates its proof of knowledge under the server's label
+        let (R, mu) = prove_knowledge::<Sha512>(
+            &mut OsRng,
+            SERVER_LABEL,
+            Phase::Dkg,
+            &c0,
+            &C0.compress(),
+            &[],
+        );
+        let mislabeled_message = DkgClientRound1 {
+            R,
+            mu,
+            ..client_dkg_message_1
+        };
+
+        assert_eq!(
+            ServerDkg::finalize_first_round::<Sha512>(&mislabeled_message),
+            Err(DkgError::RoleLabelMismatch {
+                party: Party::Client
+            }),
+        );
+    }
+
+    #[test]
+    pub fn test_round1_session_id() {
+        let session_id = b"session 1";
+        let (_, _, _, _, client_dkg_message_1) =
+            ClientDkg::start_first_round_with_session_id::<Sha512>(&mut OsRng, session_id);
+        let (_, _, _, _, server_dkg_message_1) =
+            ServerDkg::start_first_round_with_session_id::<Sha512>(&mut OsRng, session_id);
+
+        ServerDkg::finalize_first_round_with_session_id::<Sha512>(
+            &client_dkg_message_1,
+            session_id,
+        )
+        .unwrap();
+        ClientDkg::finalize_first_round_with_session_id::<Sha512>(
+            &server_dkg_message_1,
+            session_id,
+        )
+        .unwrap();
+
+        // the proofs do not verify in another session or without a session ID
+        assert_eq!(
+            ServerDkg::finalize_first_round_with_session_id::<Sha512>(
+                &client_dkg_message_1,
+                b"session 2",
+            ),
+            Err(DkgError::ProofOfKnowledge {
+                party: Party::Client
+            }),
+        );
+        assert_eq!(
+            ClientDkg::finalize_first_round::<Sha512>(&server_dkg_message_1),
+            Err(DkgError::ProofOfKnowledge {
+                party: Party::Server
+            }),
+        );
+
+        // the client's own message reflected back to it is caught by the party label
+        let reflected_message = DkgServerRound1 {
+            S0: client_dkg_message_1.C0,
+            S1: client_dkg_message_1.C1,
+            R: client_dkg_message_1.R,
+            mu: client_dkg_message_1.mu,
+        };
+        assert_eq!(
+            ClientDkg::finalize_first_round_with_session_id::<Sha512>(
+                &reflected_message,
+                session_id,
+            ),
+            Err(DkgError::RoleLabelMismatch {
+                party: Party::Server
+            }),
+        );
+    }
+
+    /// The little-endian encoding of the order `l` of the prime-order subgroup
+    fn group_order_bytes() -> [u8; 32] {
+        let mut l = [0u8; 32];
+        l[..8].copy_from_slice(&0x5812631a5cf5d3edu64.to_le_bytes());
+        l[8..16].copy_from_slice(&0x14def9dea2f79cd6u64.to_le_bytes());
+        l[31] = 0x10;
+        l
+    }
+
+    /// Runs the distributed key generation protocol and returns the private key shares along with
+    /// the client's, the server's, and the joint public keys
+    #[allow(non_snake_case)]
+    fn run_dkg() -> (
+        Scalar,
+        Scalar,
+        CompressedEdwardsY,
+        CompressedEdwardsY,
+        CompressedEdwardsY,
+    ) {
+        let (c0, c1, C0, C1, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let (s0, s1, S0, S1, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+        verify_round1_mutual::<Sha512>(&client_dkg_message_1, &server_dkg_message_1).unwrap();
+
+        let (c_client, client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
+        let (s_server, server_dkg_message_2) = ServerDkg::start_second_round(&s0, &s1);
+
+        let DkgOutput {
+            private_share: p_client,
+            own_public: P_client,
+            peer_public: P_server,
+            joint_public: P_joint,
+        } = ClientDkg::finalize_second_round(
+            &c_client,
+            &C0,
+            &C1,
+            &server_dkg_message_1,
+            &server_dkg_message_2,
+        )
+        .unwrap();
+        let DkgOutput {
+            private_share: p_server,
+            ..
+        } = ServerDkg::finalize_second_round(
+            &s_server,
+            &S0,
+            &S1,
+            &client_dkg_message_1,
+            &client_dkg_message_2,
+        )
+        .unwrap();
+
+        (
+            p_client,
+            p_server,
+            P_client.compress(),
+            P_server.compress(),
+            P_joint.compress(),
+        )
+    }
+
+    /// Runs the distributed signing protocol and returns the final signature
+    #[allow(non_snake_case)]
+    fn run_sign(
+        p_client: &Scalar,
+        p_server: &Scalar,
+        P_server: &CompressedEdwardsY,
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+    ) -> (CompressedEdwardsY, Scalar) {
+        run_sign_with_variant(
+            SigningVariant::Ed25519,
+            p_client,
+            p_server,
+            P_server,
+            P_joint,
+            message,
+        )
+    }
+
+    #[allow(non_snake_case)]
+    fn run_sign_with_variant(
+        variant: SigningVariant,
+        p_client: &Scalar,
+        p_server: &Scalar,
+        P_server: &CompressedEdwardsY,
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+    ) -> (CompressedEdwardsY, Scalar) {
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        let (_, client_sign_message_2) = ClientSign::second_round_with_variant::<Sha512>(
+            variant,
+            p_client,
+            P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round_with_variant::<Sha512>(
+            variant,
+            p_server,
+            P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        ClientSign::combine_sigs_with_variant::<Sha512>(
+            variant,
+            P_joint,
+            P_server,
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap()
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_verify_streaming() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+
+        // a message that spans many chunks and does not end on a chunk boundary
+        let message: Vec<u8> = (0..1_000_003u32).map(|i| (i % 251) as u8).collect();
+        let (R, z) = run_sign(&p_client, &p_server, &P_server, &P_joint, &message);
+
+        verify_signature::<Sha512>(&P_joint, &message, &R, &z).unwrap();
+        verify_streaming::<Sha512>(&P_joint, message.as_slice(), &R, &z).unwrap();
+
+        let mut tampered = message.clone();
+        tampered[500_000] ^= 1;
+        assert_eq!(
+            verify_signature::<Sha512>(&P_joint, &tampered, &R, &z),
+            Err(SignError::SignatureVerification),
+        );
+        assert_eq!(
+            verify_streaming::<Sha512>(&P_joint, tampered.as_slice(), &R, &z),
+            Err(SignError::SignatureVerification),
+        );
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[test]
+    pub fn test_injected_fault_surfaces_through_api() {
+        use crate::fault::{inject_dkg_fault, DkgStep};
+
+        let (_, _, _, _, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+
+        let fault = DkgError::ProofOfKnowledge {
+            party: Party::Server,
+        };
+        inject_dkg_fault(DkgStep::ClientFinalizeFirstRound, fault.clone());
+
+        // the fault is triggered once and then consumed
+        assert_eq!(
+            ClientDkg::finalize_first_round::<Sha512>(&server_dkg_message_1),
+            Err(fault),
+        );
+        ClientDkg::finalize_first_round::<Sha512>(&server_dkg_message_1).unwrap();
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_prepared_second_round_is_bound_to_message() {
+        let (p_client, _, _, _, P_joint) = run_dkg();
+        let message = b"approved message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (_, _, server_sign_message_1) = ServerSign::first_round();
+
+        let prepared = ClientSign::prepare_second_round::<Sha512>(
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ClientSign::finish_with_secret::<Sha512>(
+                &prepared,
+                b"substituted message",
+                &p_client,
+                &d_client,
+                &e_client,
+            ),
+            Err(SignError::MessageMismatch),
+        );
+
+        let (R, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        assert_eq!(prepared.nonce_commitment(), R.compress());
+        assert_eq!(
+            ClientSign::finish_with_secret::<Sha512>(
+                &prepared, message, &p_client, &d_client, &e_client,
+            ),
+            Ok(client_sign_message_2),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_signing_receipt() {
+        let (_, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+        let session_id = [7u8; 32];
+
+        let (_, _, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        let prepared = ServerSign::prepare_second_round::<Sha512>(
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let server_sign_message_2 = ServerSign::finish_with_secret::<Sha512>(
+            &prepared, message, &p_server, &d_server, &e_server,
+        )
+        .unwrap();
+
+        let receipt = issue_receipt::<Sha512>(
+            Party::Server,
+            &p_server,
+            &P_server,
+            &prepared.party_commitment(),
+            &server_sign_message_2.z_server,
+            &session_id,
+        );
+        verify_receipt::<Sha512>(&receipt, &P_server).unwrap();
+
+        let mut tampered = receipt;
+        tampered.z += Scalar::ONE;
+        assert_eq!(
+            verify_receipt::<Sha512>(&tampered, &P_server),
+            Err(SignError::SignatureVerification),
+        );
+    }
+
+    /// Runs the whole protocol with the randomness of `session` and returns the wire messages
+    /// along with the final signature
+    #[allow(non_snake_case)]
+    fn run_deterministic_session(
+        mut session: DeterministicSession,
+        message: &[u8],
+    ) -> (Vec<String>, CompressedEdwardsY, Scalar) {
+        let (c0, c1, C0, C1, client_dkg_message_1) =
+            ClientDkg::start_first_round_with_rng::<Sha512>(&mut session.client_rng);
+        let (s0, s1, S0, S1, server_dkg_message_1) =
+            ServerDkg::start_first_round_with_rng::<Sha512>(&mut session.server_rng);
+
+        let (c_client, client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
+        let (s_server, server_dkg_message_2) = ServerDkg::start_second_round(&s0, &s1);
+
+        let DkgOutput {
+            private_share: p_client,
+            peer_public: P_server,
+            joint_public: P_joint,
+            ..
+        } = ClientDkg::finalize_second_round(
+            &c_client,
+            &C0,
+            &C1,
+            &server_dkg_message_1,
+            &server_dkg_message_2,
+        )
+        .unwrap();
+        let DkgOutput {
+            private_share: p_server,
+            ..
+        } = ServerDkg::finalize_second_round(
+            &s_server,
+            &S0,
+            &S1,
+            &client_dkg_message_1,
+            &client_dkg_message_2,
+        )
+        .unwrap();
+        let P_joint = P_joint.compress();
+
+        let (d_client, e_client, client_sign_message_1) =
+            ClientSign::first_round_with_rng(&mut session.client_rng);
+        let (d_server, e_server, server_sign_message_1) =
+            ServerSign::first_round_with_rng(&mut session.server_rng);
+
+        let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round::<Sha512>(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        let (R, z) = ClientSign::combine_sigs::<Sha512>(
+            &P_joint,
+            &P_server.compress(),
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+
+        let transcript = vec![
+            client_dkg_message_1.to_string(),
+            server_dkg_message_1.to_string(),
+            client_dkg_message_2.to_string(),
+            server_dkg_message_2.to_string(),
+            client_sign_message_1.to_string(),
+            server_sign_message_1.to_string(),
+            client_sign_message_2.to_string(),
+            server_sign_message_2.to_string(),
+        ];
+        (transcript, R, z)
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_deterministic_session() {
+        let message = b"sample message";
+
+        let (transcript_1, R_1, z_1) =
+            run_deterministic_session(deterministic_session([1u8; 32]), message);
+        let (transcript_2, R_2, z_2) =
+            run_deterministic_session(deterministic_session([1u8; 32]), message);
+        assert_eq!(transcript_1, transcript_2);
+        assert_eq!(R_1, R_2);
+        assert_eq!(z_1, z_2);
+
+        let (transcript_3, R_3, _) =
+            run_deterministic_session(deterministic_session([2u8; 32]), message);
+        assert_ne!(transcript_1, transcript_3);
+        assert_ne!(R_1, R_3);
+    }
+
+    #[cfg(feature = "base58")]
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_base58_round_trip() {
+        use crate::address::{joint_key_from_base58, joint_key_to_base58, AddressError};
+
+        // the public key of the first test vector in RFC 8032, section 7.1
+        let P_joint = CompressedEdwardsY([
+            0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64,
+            0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68,
+            0xf7, 0x07, 0x51, 0x1a,
+        ]);
+        let address = "FVen3X669xLzsi6N2V91DoiyzHzg1uAgqiT8jZ9nS96Z";
+
+        assert_eq!(joint_key_to_base58(&P_joint), address);
+        assert_eq!(joint_key_from_base58(address), Ok(P_joint));
+
+        assert_eq!(joint_key_from_base58("0OIl"), Err(AddressError::Base58));
+        assert_eq!(
+            joint_key_from_base58(&address[..20]),
+            Err(AddressError::Length)
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_verify_verbose() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+        let (R, z) = run_sign(&p_client, &p_server, &P_server, &P_joint, message);
+
+        verify_verbose::<Sha512>(&P_joint, message, &R, &z).unwrap();
+
+        let diagnostics = verify_verbose::<Sha512>(&P_joint, b"other message", &R, &z).unwrap_err();
+        assert_eq!(diagnostics.error, SignError::SignatureVerification);
+        assert_eq!(diagnostics.R, R);
+        assert_ne!(diagnostics.reconstructed_R, Some(R));
+        assert!(diagnostics.reconstructed_R.is_some());
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_second_round_rejects_small_order_R() {
+        let (p_client, _, _, _, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+
+        // the server adds a point of order 8 to its commitments, which an identity check on `R`
+        // alone does not catch
+        let server_sign_message_1 = SignServerRound1 {
+            D_server: EIGHT_TORSION[1].compress(),
+            E_server: EdwardsPoint::mul_base(&Scalar::random(&mut OsRng)).compress(),
+            session_nonce: None,
+            timestamp: None,
+        };
+
+        assert_eq!(
+            ClientSign::second_round::<Sha512>(
+                &p_client,
+                &P_joint,
+                message,
+                &d_client,
+                &e_client,
+                &client_sign_message_1,
+                &server_sign_message_1,
+            ),
+            Err(SignError::WeakCommitment),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_detached_proof_of_knowledge() {
+        let (c0, _, C0, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+
+        // the proof inside the round 1 message verifies on its own against the commitment
+        verify_proof::<Sha512>(
+            &client_dkg_message_1.C0,
+            &client_dkg_message_1.proof_of_knowledge(),
+            Party::Client,
+            Phase::Dkg,
+            &[],
+        )
+        .unwrap();
+
+        // register the commitment first and prove knowledge of it later in a separate step
+        let registered_commitment = C0.compress();
+        let session_id = b"registration session";
+        let proof = create_proof::<Sha512>(
+            &mut OsRng,
+            Party::Client,
+            Phase::Dkg,
+            &c0,
+            &registered_commitment,
+            session_id,
+        );
+        verify_proof::<Sha512>(
+            &registered_commitment,
+            &proof,
+            Party::Client,
+            Phase::Dkg,
+            session_id,
+        )
+        .unwrap();
+
+        let expected_error = Err(DkgError::ProofOfKnowledge {
+            party: Party::Client,
+        });
+        assert_eq!(
+            verify_proof::<Sha512>(
+                &registered_commitment,
+                &proof,
+                Party::Client,
+                Phase::Dkg,
+                b"other"
+            ),
+            expected_error,
+        );
+        assert_eq!(
+            verify_proof::<Sha512>(
+                &client_dkg_message_1.C1,
+                &proof,
+                Party::Client,
+                Phase::Dkg,
+                session_id
+            ),
+            expected_error,
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_verify_partial_with_R() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let prepared = ServerSign::prepare_second_round::<Sha512>(
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let server_sign_message_2 = ServerSign::finish_with_secret::<Sha512>(
+            &prepared, message, &p_server, &d_server, &e_server,
+        )
+        .unwrap();
+
+        // the coordinator only keeps `R` and `R_server` around
+        let R = prepared.nonce_commitment();
+        let R_server = prepared.party_commitment();
+
+        let mut bad_server_sign_message_2 = server_sign_message_2;
+        bad_server_sign_message_2.z_server += Scalar::ONE;
+
+        for server_sign_message_2 in [server_sign_message_2, bad_server_sign_message_2] {
+            let combined = ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            );
+            let verified = verify_partial_with_R::<Sha512>(
+                Party::Server,
+                &R,
+                &R_server,
+                &P_server,
+                message,
+                &P_joint,
+                &server_sign_message_2.z_server,
+            );
+            assert_eq!(combined.map(|_| ()), verified);
+        }
+        assert_eq!(
+            verify_partial_with_R::<Sha512>(
+                Party::Server,
+                &R,
+                &R_server,
+                &P_server,
+                message,
+                &P_joint,
+                &bad_server_sign_message_2.z_server,
+            ),
+            Err(SignError::PartialSignatureVerification {
+                party: Party::Server,
+            }),
+        );
+
+        // a torsioned key share would shift the check by a small-order point that a matching
+        // shift of `R_server` cancels out, so weak key shares are rejected before the check
+        let T = EIGHT_TORSION[1];
+        let c = signing_challenge::<Sha512>(&R, message, &P_joint);
+        let shifted_R_server = (R_server.decompress().unwrap() + T * -c).compress();
+        for weak_P_server in [
+            (P_server.decompress().unwrap() + T).compress(),
+            EdwardsPoint::identity().compress(),
+        ] {
+            assert_eq!(
+                verify_partial_with_R::<Sha512>(
+                    Party::Server,
+                    &R,
+                    &shifted_R_server,
+                    &weak_P_server,
+                    message,
+                    &P_joint,
+                    &server_sign_message_2.z_server,
+                ),
+                Err(SignError::WeakPublicKey),
+            );
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_second_round_rejects_stale_commitment() {
+        let (p_client, _, _, _, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        // a commitment from an earlier session is captured
+        let (_, _, old_server_sign_message_1) =
+            ServerSign::first_round_with_session(&mut OsRng, [1u8; 32]);
+
+        let (d_client, e_client, client_sign_message_1) =
+            ClientSign::first_round_with_session(&mut OsRng, [2u8; 32]);
+        let (_, _, server_sign_message_1) =
+            ServerSign::first_round_with_session(&mut OsRng, [2u8; 32]);
+
+        ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        assert_eq!(
+            ClientSign::second_round::<Sha512>(
+                &p_client,
+                &P_joint,
+                message,
+                &d_client,
+                &e_client,
+                &client_sign_message_1,
+                &old_server_sign_message_1,
+            ),
+            Err(SignError::StaleCommitment),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_key_image() {
+        let (p_client, p_server, _, _, P_joint) = run_dkg();
+
+        let partial_client = partial_key_image::<Sha512>(&p_client, &P_joint);
+        let partial_server = partial_key_image::<Sha512>(&p_server, &P_joint);
+        let key_image = combine_key_image(&[partial_client, partial_server]).unwrap();
+
+        // the combined key image is the joint secret times the same base point
+        let p_joint = p_client + p_server;
+        assert_eq!(key_image, partial_key_image::<Sha512>(&p_joint, &P_joint));
+
+        // the key image is deterministic per key and differs across keys
+        assert_eq!(
+            key_image,
+            combine_key_image(&[partial_server, partial_client]).unwrap()
+        );
+        let (other_p_client, other_p_server, _, _, other_P_joint) = run_dkg();
+        let other_key_image = combine_key_image(&[
+            partial_key_image::<Sha512>(&other_p_client, &other_P_joint),
+            partial_key_image::<Sha512>(&other_p_server, &other_P_joint),
+        ])
+        .unwrap();
+        assert_ne!(key_image, other_key_image);
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_combine_sigs_rejects_zero_partial_signature() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round::<Sha512>(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        let zero_client_sign_message_2 = SignClientRound2 {
+            z_client: Scalar::ZERO,
+        };
+        let zero_server_sign_message_2 = SignServerRound2 {
+            z_server: Scalar::ZERO,
+        };
+
+        assert_eq!(
+            ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &zero_server_sign_message_2,
+            ),
+            Err(SignError::ZeroPartialSignature),
+        );
+        assert_eq!(
+            ServerSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_client,
+                message,
+                &client_sign_message_1,
+                &zero_client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            ),
+            Err(SignError::ZeroPartialSignature),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_verify_client_public_against() {
+        let (p_client, _, P_client, P_server, _) = run_dkg();
+        let P_client = P_client.decompress().unwrap();
+
+        // an HSM holding the client's share advertises `p_client * G`
+        let hsm_public = EdwardsPoint::mul_base(&p_client).compress();
+        verify_client_public_against(&P_client, &hsm_public).unwrap();
+
+        // an HSM holding a different key
+        assert_eq!(
+            verify_client_public_against(&P_client, &P_server),
+            Err(DkgError::KeyMismatch),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_prepared_combiner() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let client_combiner = PreparedCombiner::new(&P_joint, &P_server).unwrap();
+        let server_combiner = PreparedCombiner::new(&P_joint, &P_client).unwrap();
+
+        for message in [&b"first message"[..], &b"second message"[..]] {
+            let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+            let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+            let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+                &p_client,
+                &P_joint,
+                message,
+                &d_client,
+                &e_client,
+                &client_sign_message_1,
+                &server_sign_message_1,
+            )
+            .unwrap();
+            let (_, server_sign_message_2) = ServerSign::second_round::<Sha512>(
+                &p_server,
+                &P_joint,
+                message,
+                &d_server,
+                &e_server,
+                &client_sign_message_1,
+                &server_sign_message_1,
+            )
+            .unwrap();
+
+            let client_signature = ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            );
+            let server_signature = ServerSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_client,
+                message,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            );
+            assert_eq!(
+                ClientSign::combine_sigs_prepared::<Sha512>(
+                    &client_combiner,
+                    message,
+                    &client_sign_message_1,
+                    &client_sign_message_2,
+                    &server_sign_message_1,
+                    &server_sign_message_2,
+                ),
+                client_signature,
+            );
+            assert_eq!(
+                ServerSign::combine_sigs_prepared::<Sha512>(
+                    &server_combiner,
+                    message,
+                    &client_sign_message_1,
+                    &client_sign_message_2,
+                    &server_sign_message_1,
+                    &server_sign_message_2,
+                ),
+                server_signature,
+            );
+
+            // a bad partial signature is rejected the same way
+            let mut bad_server_sign_message_2 = server_sign_message_2;
+            bad_server_sign_message_2.z_server += Scalar::ONE;
+            assert_eq!(
+                ClientSign::combine_sigs_prepared::<Sha512>(
+                    &client_combiner,
+                    message,
+                    &client_sign_message_1,
+                    &client_sign_message_2,
+                    &server_sign_message_1,
+                    &bad_server_sign_message_2,
+                ),
+                Err(SignError::PartialSignatureVerification {
+                    party: Party::Server,
+                }),
+            );
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_replayed_dkg_share() {
+        // a session whose round 2 share gets captured
+        let (c0, c1, _, _, _) = ClientDkg::start_first_round::<Sha512>();
+        let (_, captured_client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
+
+        // a fresh session
+        let (_, _, _, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let (s0, s1, S0, S1, _) = ServerDkg::start_first_round::<Sha512>();
+        let (s_server, _) = ServerDkg::start_second_round(&s0, &s1);
+
+        // the share is bound to the round 1 commitments of its own session by
+        // `c_server * G = C0 - C1`
+        assert_eq!(
+            ServerDkg::finalize_second_round(
+                &s_server,
+                &S0,
+                &S1,
+                &client_dkg_message_1,
+                &captured_client_dkg_message_2,
+            ),
+            Err(DkgError::ShareVerification),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_check_timestamp() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+        let now = 1_700_000_000;
+        let max_skew = 300;
+
+        let (d_client, e_client, client_sign_message_1) =
+            ClientSign::first_round_with_timestamp(&mut OsRng, now - 10);
+        let (d_server, e_server, server_sign_message_1) =
+            ServerSign::first_round_with_timestamp(&mut OsRng, now);
+
+        check_timestamp(client_sign_message_1.timestamp, now, max_skew).unwrap();
+        check_timestamp(Some(now + max_skew), now, max_skew).unwrap();
+
+        // expired, from the future and missing timestamps
+        for timestamp in [Some(now - max_skew - 1), Some(now + max_skew + 1), None] {
+            assert_eq!(
+                check_timestamp(timestamp, now, max_skew),
+                Err(SignError::TimestampOutOfRange),
+            );
+        }
+
+        // the client refuses to sign an expired server message
+        let (_, _, expired_server_sign_message_1) =
+            ServerSign::first_round_with_timestamp(&mut OsRng, now - max_skew - 1);
+        assert_eq!(
+            ClientSign::second_round_with_timestamp::<Sha512>(
+                &p_client,
+                &P_joint,
+                message,
+                &d_client,
+                &e_client,
+                &client_sign_message_1,
+                &expired_server_sign_message_1,
+                now,
+                max_skew,
+            )
+            .map(|_| ()),
+            Err(SignError::TimestampOutOfRange),
+        );
+
+        let (_, client_sign_message_2) = ClientSign::second_round_with_timestamp::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+            now,
+            max_skew,
+        )
+        .unwrap();
+
+        // the server refuses to sign an expired client message
+        let (_, _, expired_client_sign_message_1) =
+            ClientSign::first_round_with_timestamp(&mut OsRng, now - max_skew - 1);
+        assert_eq!(
+            ServerSign::second_round_with_timestamp::<Sha512>(
+                &p_server,
+                &P_joint,
+                message,
+                &d_server,
+                &e_server,
+                &expired_client_sign_message_1,
+                &server_sign_message_1,
+                now,
+                max_skew,
+            )
+            .map(|_| ()),
+            Err(SignError::TimestampOutOfRange),
+        );
+
+        // the server signs with a timestamp that was altered in transit
+        let mut altered_server_sign_message_1 = server_sign_message_1;
+        altered_server_sign_message_1.timestamp = Some(now - 10);
+        let (_, server_sign_message_2) = ServerSign::second_round_with_timestamp::<Sha512>(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &altered_server_sign_message_1,
+            now,
+            max_skew,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            ),
+            Err(SignError::PartialSignatureVerification {
+                party: Party::Server,
+            }),
+        );
+    }
+
+    #[test]
+    pub fn test_derive_session_id() {
+        let (_, _, client_sign_message_1) = ClientSign::first_round();
+        let (_, _, server_sign_message_1) = ServerSign::first_round();
+
+        // each party derives the ID from its own copy of the messages
+        let client_session_id =
+            derive_session_id::<Sha512>(&client_sign_message_1, &server_sign_message_1);
+        let (received_client_sign_message_1, received_server_sign_message_1) =
+            (client_sign_message_1, server_sign_message_1);
+        let server_session_id = derive_session_id::<Sha512>(
+            &received_client_sign_message_1,
+            &received_server_sign_message_1,
+        );
+        assert_eq!(client_session_id, server_session_id);
+
+        let (_, _, other_client_sign_message_1) = ClientSign::first_round();
+        assert_ne!(
+            client_session_id,
+            derive_session_id::<Sha512>(&other_client_sign_message_1, &server_sign_message_1),
+        );
+    }
+
+    #[test]
+    pub fn test_proof_of_knowledge_phase() {
+        let (_, _, _, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let proof = client_dkg_message_1.proof_of_knowledge();
+
+        verify_proof::<Sha512>(
+            &client_dkg_message_1.C0,
+            &proof,
+            Party::Client,
+            Phase::Dkg,
+            &[],
+        )
+        .unwrap();
+
+        // a DKG proof presented to a share refresh verifier
+        assert_eq!(
+            verify_proof::<Sha512>(
+                &client_dkg_message_1.C0,
+                &proof,
+                Party::Client,
+                Phase::Refresh,
+                &[],
+            ),
+            Err(DkgError::ProofOfKnowledge {
+                party: Party::Client
+            }),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_verify_reconstructed_secret() {
+        let (p_client, p_server, _, _, P_joint) = run_dkg();
+
+        verify_reconstructed_secret(&(p_client + p_server), &P_joint).unwrap();
+
+        // a corrupted share
+        assert_eq!(
+            verify_reconstructed_secret(&(p_client + p_server + Scalar::ONE), &P_joint),
+            Err(DkgError::KeyMismatch),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_r_y_and_parity() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let (R, _) = run_sign(&p_client, &p_server, &P_server, &P_joint, b"sample message");
+
+        let (coordinate, parity) = r_y_and_parity(&R);
+        assert_eq!(coordinate[31] & 0x80, 0);
+        assert_eq!(r_from_y_and_parity(&coordinate, parity).unwrap(), R);
+
+        // the parity bit selects between `R` and its negation
+        let R_point = R.decompress().unwrap();
+        assert_eq!(
+            r_y_and_parity(&(-R_point).compress()),
+            (coordinate, !parity)
+        );
+        assert_eq!(
+            r_from_y_and_parity(&coordinate, !parity).unwrap(),
+            (-R_point).compress()
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_second_round_with_policy() {
+        let (_, p_server, _, _, P_joint) = run_dkg();
+        let policy = |message: &[u8]| message.starts_with(b"allowed");
+
+        let (_, _, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        let (R, server_sign_message_2) = ServerSign::second_round_with_policy::<Sha512>(
+            &p_server,
+            &P_joint,
+            b"allowed message",
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+            policy,
+        )
+        .unwrap();
+        assert_eq!(
+            ServerSign::second_round::<Sha512>(
+                &p_server,
+                &P_joint,
+                b"allowed message",
+                &d_server,
+                &e_server,
+                &client_sign_message_1,
+                &server_sign_message_1,
+            ),
+            Ok((R, server_sign_message_2)),
+        );
+
+        assert_eq!(
+            ServerSign::second_round_with_policy::<Sha512>(
+                &p_server,
+                &P_joint,
+                b"forbidden message",
+                &d_server,
+                &e_server,
+                &client_sign_message_1,
+                &server_sign_message_1,
+                policy,
+            ),
+            Err(SignError::PolicyRejected),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_joint_key_to_montgomery() {
+        // the public key of the first test vector in RFC 8032, section 7.1
+        let P_joint = CompressedEdwardsY([
+            0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64,
+            0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68,
+            0xf7, 0x07, 0x51, 0x1a,
+        ]);
+        let expected = [
+            0xd8, 0x5e, 0x07, 0xec, 0x22, 0xb0, 0xad, 0x88, 0x15, 0x37, 0xc2, 0xf4, 0x4d, 0x66,
+            0x2d, 0x1a, 0x14, 0x3c, 0xf8, 0x30, 0xc5, 0x7a, 0xca, 0x43, 0x05, 0xd8, 0x5c, 0x7a,
+            0x90, 0xf6, 0xb6, 0x2e,
+        ];
+        assert_eq!(joint_key_to_montgomery(&P_joint).unwrap(), expected);
+
+        // the map commutes with the unclamped scalar multiplication of the base point
+        let (p_client, p_server, _, _, P_joint) = run_dkg();
+        assert_eq!(
+            joint_key_to_montgomery(&P_joint).unwrap(),
+            MontgomeryPoint::mul_base(&(p_client + p_server)).to_bytes(),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_combine_sigs_verified() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round::<Sha512>(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        let signature = ClientSign::combine_sigs_verified::<Sha512>(
+            &P_joint,
+            &P_server,
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        );
+        assert_eq!(
+            signature,
+            ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            ),
+        );
+
+        // the client's own partial signature is corrupted between the second round and combining,
+        // which `combine_sigs` does not check
+        let mut corrupted_client_sign_message_2 = client_sign_message_2;
+        corrupted_client_sign_message_2.z_client += Scalar::ONE;
+        ClientSign::combine_sigs::<Sha512>(
+            &P_joint,
+            &P_server,
+            message,
+            &client_sign_message_1,
+            &corrupted_client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+        assert_eq!(
+            ClientSign::combine_sigs_verified::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &client_sign_message_1,
+                &corrupted_client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            ),
+            Err(SignError::AggregateInvalid),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_secret_store() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let client_store = MemorySecretStore::new(p_client);
+        let mut server_store = MemorySecretStore::new(Scalar::ZERO);
+        server_store.store(p_server);
+        assert_eq!(server_store.load(), p_server);
+
+        let message = b"sample message";
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        // the shares are only borrowed from the stores for the duration of the second round
+        let (_, client_sign_message_2) = client_store
+            .use_share(|p_client| {
+                ClientSign::second_round::<Sha512>(
+                    p_client,
+                    &P_joint,
+                    message,
+                    &d_client,
+                    &e_client,
+                    &client_sign_message_1,
+                    &server_sign_message_1,
+                )
+            })
+            .unwrap();
+        let (_, server_sign_message_2) = server_store
+            .use_share(|p_server| {
+                ServerSign::second_round::<Sha512>(
+                    p_server,
+                    &P_joint,
+                    message,
+                    &d_server,
+                    &e_server,
+                    &client_sign_message_1,
+                    &server_sign_message_1,
+                )
+            })
+            .unwrap();
+
+        let (R, z) = ClientSign::combine_sigs::<Sha512>(
+            &P_joint,
+            &P_server,
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_joint_key_fingerprint() {
+        // the public key of the first test vector in RFC 8032, section 7.1
+        let P_joint = CompressedEdwardsY([
+            0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64,
+            0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68,
+            0xf7, 0x07, 0x51, 0x1a,
+        ]);
+        assert_eq!(joint_key_fingerprint(&P_joint), "21:fe:31:df:a1:54:a2:61");
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_aggregate_partials() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+        let session_id =
+            derive_session_id::<Sha512>(&client_sign_message_1, &server_sign_message_1);
+
+        let client_prepared = ClientSign::prepare_second_round::<Sha512>(
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let client_sign_message_2 = ClientSign::finish_with_secret::<Sha512>(
+            &client_prepared,
+            message,
+            &p_client,
+            &d_client,
+            &e_client,
+        )
+        .unwrap();
+        let server_prepared = ServerSign::prepare_second_round::<Sha512>(
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let server_sign_message_2 = ServerSign::finish_with_secret::<Sha512>(
+            &server_prepared,
+            message,
+            &p_server,
+            &d_server,
+            &e_server,
+        )
+        .unwrap();
+
+        let client_partial = PartialSignatureMessage {
+            role: Party::Client,
+            R_party: client_prepared.party_commitment(),
+            z: client_sign_message_2.z_client,
+            session_id,
+        };
+        let server_partial = PartialSignatureMessage {
+            role: Party::Server,
+            R_party: server_prepared.party_commitment(),
+            z: server_sign_message_2.z_server,
+            session_id,
+        };
+
+        let signature = aggregate_partials::<Sha512>(
+            &client_partial,
+            &server_partial,
+            &P_client,
+            &P_server,
+            message,
+        )
+        .unwrap();
+        let R = CompressedEdwardsY(signature[..32].try_into().unwrap());
+        let z = Scalar::from_canonical_bytes(signature[32..].try_into().unwrap()).unwrap();
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+
+        // the coordinator catches a bad partial signature
+        let mut bad_server_partial = server_partial;
+        bad_server_partial.z += Scalar::ONE;
+        assert_eq!(
+            aggregate_partials::<Sha512>(
+                &client_partial,
+                &bad_server_partial,
+                &P_client,
+                &P_server,
+                message,
+            ),
+            Err(SignError::PartialSignatureVerification {
+                party: Party::Server,
+            }),
+        );
+
+        // and names the client when its partial signature is the bad one
+        let mut bad_client_partial = client_partial;
+        bad_client_partial.z += Scalar::ONE;
+        assert_eq!(
+            aggregate_partials::<Sha512>(
+                &bad_client_partial,
+                &server_partial,
+                &P_client,
+                &P_server,
+                message,
+            ),
+            Err(SignError::PartialSignatureVerification {
+                party: Party::Client,
+            }),
+        );
+
+        // and partial signatures passed in the wrong order
+        assert_eq!(
+            aggregate_partials::<Sha512>(
+                &server_partial,
+                &client_partial,
+                &P_client,
+                &P_server,
+                message,
+            ),
+            Err(SignError::UnexpectedParty),
+        );
+    }
+
+    #[test]
+    pub fn test_conformance_vectors() {
+        let message = b"sample message";
+
+        let vectors = conformance_vectors([1u8; 32], message);
+        assert_eq!(vectors, conformance_vectors([1u8; 32], message));
+        assert_ne!(vectors, conformance_vectors([2u8; 32], message));
+        assert!(vectors.contains(&format!("\"seed\": \"{}\"", "01".repeat(32))));
+
+        // known answers for the seeded run, which change with any change to the hashing order
+        for field in [
+            "\"P_joint\": \"bdb5d975c1bd8b0db1fda4504dba8a7788cb13c2483c7e35d00d1462576f0643\"",
+            "\"challenge\": \"546d60c6a2a32c245b5f2b17632df16ceb3feb2d045c4028a9e7cb09f73fd801\"",
+            "\"signature_R\": \"b1e2082aebe5465fb613f1d1420c7ca1e72f1a32a21bd075e0085eafbdecd867\"",
+            "\"signature_z\": \"67e7e5d5eeb1adb0514609b1006aee4a795b0dea1f0e8a331759233f73cbec07\"",
+        ] {
+            assert!(vectors.contains(field), "missing {field}");
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_combine_sigs_attributes_torsion() {
+        let (p_client, _, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (_, _, server_sign_message_1) = ServerSign::first_round();
+        let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        // the server adds a torsion component to its commitment
+        let mut server_sign_message_1 = server_sign_message_1;
+        server_sign_message_1.D_server =
+            (server_sign_message_1.D_server.decompress().unwrap() + EIGHT_TORSION[1]).compress();
+        let server_sign_message_2 = SignServerRound2 {
+            z_server: Scalar::ONE,
+        };
+
+        assert_eq!(
+            ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            ),
+            Err(SignError::WeakPartyCommitment {
+                party: Party::Server
+            }),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_aggregation_proof() {
+        let (_, _, P_client, P_server, P_joint) = run_dkg();
+
+        let proof = prove_aggregation(&P_client, &P_server);
+        verify_aggregation(&P_joint, &proof).unwrap();
+
+        // shares of a different key
+        let (_, _, other_P_client, _, _) = run_dkg();
+        assert_eq!(
+            verify_aggregation(&P_joint, &prove_aggregation(&other_P_client, &P_server)),
+            Err(DkgError::KeyMismatch),
+        );
+
+        // a torsion-shifted split of the same joint key
+        let T = EIGHT_TORSION[1];
+        let shifted_P_client = (P_client.decompress().unwrap() + T).compress();
+        let shifted_P_server = (P_server.decompress().unwrap() - T).compress();
+        assert_eq!(
+            verify_aggregation(
+                &P_joint,
+                &prove_aggregation(&shifted_P_client, &shifted_P_server)
+            ),
+            Err(DkgError::WeakCommitment {
+                party: Party::Client
+            }),
+        );
+
+        // the whole joint key on one side
+        assert_eq!(
+            verify_aggregation(
+                &P_joint,
+                &prove_aggregation(&P_joint, &EdwardsPoint::identity().compress())
+            ),
+            Err(DkgError::WeakCommitment {
+                party: Party::Server
+            }),
+        );
+    }
+
+    #[test]
+    pub fn test_message_bytes_round_trip() {
+        let (c0, c1, _, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let (s0, s1, _, _, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+        let (_, client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
+        let (_, server_dkg_message_2) = ServerDkg::start_second_round(&s0, &s1);
+
+        let (_, _, mut client_sign_message_1) = ClientSign::first_round();
+        let (_, _, server_sign_message_1) =
+            ServerSign::first_round_with_session(&mut OsRng, [7u8; 32]);
+        client_sign_message_1.timestamp = Some(1_700_000_000);
+        let client_sign_message_2 = SignClientRound2 {
+            z_client: Scalar::random(&mut OsRng),
+        };
+        let server_sign_message_2 = SignServerRound2 {
+            z_server: Scalar::random(&mut OsRng),
+        };
+
+        let bytes = client_dkg_message_1.to_bytes();
+        assert_eq!(bytes.len(), DkgClientRound1::LENGTH);
+        assert_eq!(
+            DkgClientRound1::from_bytes(&bytes),
+            Ok(client_dkg_message_1)
+        );
+        assert_eq!(
+            DkgServerRound1::from_bytes(&server_dkg_message_1.to_bytes()),
+            Ok(server_dkg_message_1)
+        );
+        assert_eq!(
+            DkgClientRound2::from_bytes(&client_dkg_message_2.to_bytes()),
+            Ok(client_dkg_message_2)
+        );
+        assert_eq!(
+            DkgServerRound2::from_bytes(&server_dkg_message_2.to_bytes()),
+            Ok(server_dkg_message_2)
+        );
+        assert_eq!(
+            SignClientRound1::from_bytes(&client_sign_message_1.to_bytes()),
+            Ok(client_sign_message_1)
+        );
+        assert_eq!(
+            SignServerRound1::from_bytes(&server_sign_message_1.to_bytes()),
+            Ok(server_sign_message_1)
+        );
+        assert_eq!(
+            SignClientRound2::from_bytes(&client_sign_message_2.to_bytes()),
+            Ok(client_sign_message_2)
+        );
+        assert_eq!(
+            SignServerRound2::from_bytes(&server_sign_message_2.to_bytes()),
+            Ok(server_sign_message_2)
+        );
+
+        // truncated and overlong input
+        let bytes = client_dkg_message_1.to_bytes();
+        assert_eq!(
+            DkgClientRound1::from_bytes(&bytes[..127]),
+            Err(DkgError::MalformedMessage)
+        );
+        let bytes = client_sign_message_1.to_bytes();
+        assert_eq!(
+            SignClientRound1::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(SignError::MalformedMessage)
+        );
+        assert_eq!(
+            SignClientRound1::from_bytes(&[bytes.as_slice(), &[0]].concat()),
+            Err(SignError::MalformedMessage)
+        );
+
+        // unknown flags
+        let mut bytes = server_sign_message_1.to_bytes();
+        bytes[64] |= 4;
+        assert_eq!(
+            SignServerRound1::from_bytes(&bytes),
+            Err(SignError::MalformedMessage)
+        );
+
+        // a non-canonical scalar
+        let mut bytes = client_dkg_message_1.to_bytes();
+        bytes[96..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            DkgClientRound1::from_bytes(&bytes),
+            Err(DkgError::NonCanonicalScalar)
+        );
+        assert_eq!(
+            SignClientRound2::from_bytes(&[0xff; 32]),
+            Err(SignError::NonCanonicalScalar)
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_verify_streaming_with_limit() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = vec![7u8; 100_000];
+        let (R, z) = run_sign(&p_client, &p_server, &P_server, &P_joint, &message);
+
+        verify_streaming_with_limit::<Sha512>(&P_joint, message.as_slice(), &R, &z, 100_000)
+            .unwrap();
+        assert_eq!(
+            verify_streaming_with_limit::<Sha512>(&P_joint, message.as_slice(), &R, &z, 99_999),
+            Err(SignError::MessageTooLarge),
+        );
+
+        // an endless stream is cut off at the limit instead of being hashed forever
+        assert_eq!(
+            verify_streaming_with_limit::<Sha512>(&P_joint, std::io::repeat(7), &R, &z, 1 << 20),
+            Err(SignError::MessageTooLarge),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_challenge_matches_rfc8032() {
+        // test vector 3 of RFC 8032, section 7.1, which hashes the challenge as `R || A || M`
+        let P_joint = CompressedEdwardsY([
+            0xfc, 0x51, 0xcd, 0x8e, 0x62, 0x18, 0xa1, 0xa3, 0x8d, 0xa4, 0x7e, 0xd0, 0x02, 0x30,
+            0xf0, 0x58, 0x08, 0x16, 0xed, 0x13, 0xba, 0x33, 0x03, 0xac, 0x5d, 0xeb, 0x91, 0x15,
+            0x48, 0x90, 0x80, 0x25,
+        ]);
+        let message = [0xaf, 0x82];
+        let R = CompressedEdwardsY([
+            0x62, 0x91, 0xd6, 0x57, 0xde, 0xec, 0x24, 0x02, 0x48, 0x27, 0xe6, 0x9c, 0x3a, 0xbe,
+            0x01, 0xa3, 0x0c, 0xe5, 0x48, 0xa2, 0x84, 0x74, 0x3a, 0x44, 0x5e, 0x36, 0x80, 0xd7,
+            0xdb, 0x5a, 0xc3, 0xac,
+        ]);
+        let z = Scalar::from_canonical_bytes([
+            0x18, 0xff, 0x9b, 0x53, 0x8d, 0x16, 0xf2, 0x90, 0xae, 0x67, 0xf7, 0x60, 0x98, 0x4d,
+            0xc6, 0x59, 0x4a, 0x7c, 0x15, 0xe9, 0x71, 0x6e, 0xd2, 0x8d, 0xc0, 0x27, 0xbe, 0xce,
+            0xea, 0x1e, 0xc4, 0x0a,
+        ])
+        .unwrap();
+
+        verify_signature::<Sha512>(&P_joint, &message, &R, &z).unwrap();
+        verify_streaming::<Sha512>(&P_joint, message.as_slice(), &R, &z).unwrap();
+        verify_verbose::<Sha512>(&P_joint, &message, &R, &z).unwrap();
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_verify_external_signature() {
+        // the first two test vectors in RFC 8032, section 7.1
+        let vectors: [([u8; 32], &[u8], [u8; 64]); 2] = [
+            (
+                [
+                    0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9,
+                    0x64, 0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02,
+                    0x1a, 0x68, 0xf7, 0x07, 0x51, 0x1a,
+                ],
+                b"",
+                [
+                    0xe5, 0x56, 0x43, 0x00, 0xc3, 0x60, 0xac, 0x72, 0x90, 0x86, 0xe2, 0xcc, 0x80,
+                    0x6e, 0x82, 0x8a, 0x84, 0x87, 0x7f, 0x1e, 0xb8, 0xe5, 0xd9, 0x74, 0xd8, 0x73,
+                    0xe0, 0x65, 0x22, 0x49, 0x01, 0x55, 0x5f, 0xb8, 0x82, 0x15, 0x90, 0xa3, 0x3b,
+                    0xac, 0xc6, 0x1e, 0x39, 0x70, 0x1c, 0xf9, 0xb4, 0x6b, 0xd2, 0x5b, 0xf5, 0xf0,
+                    0x59, 0x5b, 0xbe, 0x24, 0x65, 0x51, 0x41, 0x43, 0x8e, 0x7a, 0x10, 0x0b,
+                ],
+            ),
+            (
+                [
+                    0x3d, 0x40, 0x17, 0xc3, 0xe8, 0x43, 0x89, 0x5a, 0x92, 0xb7, 0x0a, 0xa7, 0x4d,
+                    0x1b, 0x7e, 0xbc, 0x9c, 0x98, 0x2c, 0xcf, 0x2e, 0xc4, 0x96, 0x8c, 0xc0, 0xcd,
+                    0x55, 0xf1, 0x2a, 0xf4, 0x66, 0x0c,
+                ],
+                &[0x72],
+                [
+                    0x92, 0xa0, 0x09, 0xa9, 0xf0, 0xd4, 0xca, 0xb8, 0x72, 0x0e, 0x82, 0x0b, 0x5f,
+                    0x64, 0x25, 0x40, 0xa2, 0xb2, 0x7b, 0x54, 0x16, 0x50, 0x3f, 0x8f, 0xb3, 0x76,
+                    0x22, 0x23, 0xeb, 0xdb, 0x69, 0xda, 0x08, 0x5a, 0xc1, 0xe4, 0x3e, 0x15, 0x99,
+                    0x6e, 0x45, 0x8f, 0x36, 0x13, 0xd0, 0xf1, 0x1d, 0x8c, 0x38, 0x7b, 0x2e, 0xae,
+                    0xb4, 0x30, 0x2a, 0xee, 0xb0, 0x0d, 0x29, 0x16, 0x12, 0xbb, 0x0c, 0x00,
+                ],
+            ),
+        ];
+
+        for (public_key, message, signature) in vectors {
+            let P_joint = CompressedEdwardsY(public_key);
+            verify_external_signature(&P_joint, message, &signature).unwrap();
+            assert_eq!(
+                verify_external_signature(&P_joint, b"other message", &signature),
+                Err(SignError::SignatureVerification),
+            );
+        }
+
+        // and in the other direction, a signature of the protocol in the standard encoding
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+        let (R, z) = run_sign(&p_client, &p_server, &P_server, &P_joint, message);
+        verify_external_signature(&P_joint, message, &to_signature_bytes(&R, &z)).unwrap();
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_to_signature_bytes() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+        let (R, z) = run_sign(&p_client, &p_server, &P_server, &P_joint, message);
+
+        // `R || s`, verified with the RFC 8032 verifier
+        let signature = to_signature_bytes(&R, &z);
+        assert_eq!(&signature[..32], R.as_bytes());
+        assert_eq!(&signature[32..], z.as_bytes());
+        verify_external_signature(&P_joint, message, &signature).unwrap();
+        assert_eq!(
+            verify_external_signature(&P_joint, b"other message", &signature),
+            Err(SignError::SignatureVerification),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_prehash_signing() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let digest = prehash(b"sample message");
+        let variant = SigningVariant::Ed25519ph { context: b"" };
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        let (_, client_sign_message_2) = ClientSign::second_round_with_variant::<Sha512>(
+            variant,
+            &p_client,
+            &P_joint,
+            &digest,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round_with_variant::<Sha512>(
+            variant,
+            &p_server,
+            &P_joint,
+            &digest,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        // both parties combine to the same prehashed signature
+        let (R, z) = ClientSign::combine_sigs_with_variant::<Sha512>(
+            variant,
+            &P_joint,
+            &P_server,
+            &digest,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+        assert_eq!(
+            ServerSign::combine_sigs_with_variant::<Sha512>(
+                variant,
+                &P_joint,
+                &P_client,
+                &digest,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            )
+            .unwrap(),
+            (R, z),
+        );
+        verify_signature_with_variant::<Sha512>(variant, &P_joint, &digest, &R, &z).unwrap();
+
+        // the prehashed signature is not a plain signature over the digest
+        assert_eq!(
+            verify_signature::<Sha512>(&P_joint, &digest, &R, &z),
+            Err(SignError::SignatureVerification),
+        );
+        assert_eq!(
+            ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                &digest,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            ),
+            Err(SignError::PartialSignatureVerification {
+                party: Party::Server,
+            }),
+        );
+
+        // RFC 8032 Ed25519ph test vector "abc"
+        let P = CompressedEdwardsY([
+            0xec, 0x17, 0x2b, 0x93, 0xad, 0x5e, 0x56, 0x3b, 0xf4, 0x93, 0x2c, 0x70, 0xe1, 0x24,
+            0x50, 0x34, 0xc3, 0x54, 0x67, 0xef, 0x2e, 0xfd, 0x4d, 0x64, 0xeb, 0xf8, 0x19, 0x68,
+            0x34, 0x67, 0xe2, 0xbf,
+        ]);
+        let signature: [u8; 64] = [
+            0x98, 0xa7, 0x02, 0x22, 0xf0, 0xb8, 0x12, 0x1a, 0xa9, 0xd3, 0x0f, 0x81, 0x3d, 0x68,
+            0x3f, 0x80, 0x9e, 0x46, 0x2b, 0x46, 0x9c, 0x7f, 0xf8, 0x76, 0x39, 0x49, 0x9b, 0xb9,
+            0x4e, 0x6d, 0xae, 0x41, 0x31, 0xf8, 0x50, 0x42, 0x46, 0x3c, 0x2a, 0x35, 0x5a, 0x20,
+            0x03, 0xd0, 0x62, 0xad, 0xf5, 0xaa, 0xa1, 0x0b, 0x8c, 0x61, 0xe6, 0x36, 0x06, 0x2a,
+            0xaa, 0xd1, 0x1c, 0x2a, 0x26, 0x08, 0x34, 0x06,
+        ];
+        let R = CompressedEdwardsY(signature[..32].try_into().unwrap());
+        let z = Scalar::from_canonical_bytes(signature[32..].try_into().unwrap()).unwrap();
+        verify_signature_with_variant::<Sha512>(variant, &P, &prehash(b"abc"), &R, &z).unwrap();
+
+        // the context is at most 255 bytes long
+        assert_eq!(
+            verify_signature_with_variant::<Sha512>(
+                SigningVariant::Ed25519ph {
+                    context: &[0u8; 256]
+                },
+                &P,
+                &prehash(b"abc"),
+                &R,
+                &z,
+            ),
+            Err(SignError::ContextTooLong),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_context_signing() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+        let app_a = SigningVariant::Ed25519ctx { context: b"app a" };
+        let app_b = SigningVariant::Ed25519ctx { context: b"app b" };
+
+        let (R, z) =
+            run_sign_with_variant(app_a, &p_client, &p_server, &P_server, &P_joint, message);
+        verify_signature_with_variant::<Sha512>(app_a, &P_joint, message, &R, &z).unwrap();
+
+        // a signature for one application does not verify for another with the same key
+        assert_eq!(
+            verify_signature_with_variant::<Sha512>(app_b, &P_joint, message, &R, &z),
+            Err(SignError::SignatureVerification),
+        );
+        assert_eq!(
+            verify_signature::<Sha512>(&P_joint, message, &R, &z),
+            Err(SignError::SignatureVerification),
+        );
+        let (R, z) =
+            run_sign_with_variant(app_b, &p_client, &p_server, &P_server, &P_joint, message);
+        assert_eq!(
+            verify_signature_with_variant::<Sha512>(app_a, &P_joint, message, &R, &z),
+            Err(SignError::SignatureVerification),
+        );
+
+        // RFC 8032 Ed25519ctx test vector "foo"
+        let P = CompressedEdwardsY([
+            0xdf, 0xc9, 0x42, 0x5e, 0x4f, 0x96, 0x8f, 0x7f, 0x0c, 0x29, 0xf0, 0x25, 0x9c, 0xf5,
+            0xf9, 0xae, 0xd6, 0x85, 0x1c, 0x2b, 0xb4, 0xad, 0x8b, 0xfb, 0x86, 0x0c, 0xfe, 0xe0,
+            0xab, 0x24, 0x82, 0x92,
+        ]);
+        let message = [
+            0xf7, 0x26, 0x93, 0x6d, 0x19, 0xc8, 0x00, 0x49, 0x4e, 0x3f, 0xda, 0xff, 0x20, 0xb2,
+            0x76, 0xa8,
+        ];
+        let signature: [u8; 64] = [
+            0x55, 0xa4, 0xcc, 0x2f, 0x70, 0xa5, 0x4e, 0x04, 0x28, 0x8c, 0x5f, 0x4c, 0xd1, 0xe4,
+            0x5a, 0x7b, 0xb5, 0x20, 0xb3, 0x62, 0x92, 0x91, 0x18, 0x76, 0xca, 0xda, 0x73, 0x23,
+            0x19, 0x8d, 0xd8, 0x7a, 0x8b, 0x36, 0x95, 0x0b, 0x95, 0x13, 0x00, 0x22, 0x90, 0x7a,
+            0x7f, 0xb7, 0xc4, 0xe9, 0xb2, 0xd5, 0xf6, 0xcc, 0xa6, 0x85, 0xa5, 0x87, 0xb4, 0xb2,
+            0x1f, 0x4b, 0x88, 0x8e, 0x4e, 0x7e, 0xdb, 0x0d,
+        ];
+        let R = CompressedEdwardsY(signature[..32].try_into().unwrap());
+        let z = Scalar::from_canonical_bytes(signature[32..].try_into().unwrap()).unwrap();
+        let foo = SigningVariant::Ed25519ctx { context: b"foo" };
+        verify_signature_with_variant::<Sha512>(foo, &P, &message, &R, &z).unwrap();
+
+        // Ed25519ctx requires a non-empty context
+        assert_eq!(
+            verify_signature_with_variant::<Sha512>(
+                SigningVariant::Ed25519ctx { context: b"" },
+                &P,
+                &message,
+                &R,
+                &z,
+            ),
+            Err(SignError::EmptyContext),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_session_nonce_binds_signature() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) =
+            ClientSign::first_round_with_session(&mut OsRng, [2u8; 32]);
+        let (d_server, e_server, server_sign_message_1) =
+            ServerSign::first_round_with_session(&mut OsRng, [2u8; 32]);
+
+        let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round::<Sha512>(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        let (R, z) = ClientSign::combine_sigs::<Sha512>(
+            &P_joint,
+            &P_server,
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+
+        // the same commitments relabeled with another session give a different `R`, so the
+        // partial signatures of the original session do not combine under it
+        let relabeled_client_message_1 = SignClientRound1 {
+            session_nonce: Some([3u8; 32]),
+            ..client_sign_message_1
+        };
+        let relabeled_server_message_1 = SignServerRound1 {
+            session_nonce: Some([3u8; 32]),
+            ..server_sign_message_1
+        };
+        assert_eq!(
+            ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &relabeled_client_message_1,
+                &client_sign_message_2,
+                &relabeled_server_message_1,
+                &server_sign_message_2,
+            ),
+            Err(SignError::PartialSignatureVerification {
+                party: Party::Server,
+            }),
+        );
+
+        // round 1 messages of different sessions are rejected outright
+        assert_eq!(
+            ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &relabeled_server_message_1,
+                &server_sign_message_2,
+            ),
+            Err(SignError::StaleCommitment),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_key_share() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let client_share = KeyShare::new(DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_server.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        let server_share = KeyShare::from(DkgOutput {
+            private_share: p_server,
+            own_public: P_server.decompress().unwrap(),
+            peer_public: P_client.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        let (_, client_sign_message_2) = ClientSign::second_round_with_share::<Sha512>(
+            &client_share,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round_with_share::<Sha512>(
+            &server_share,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        let (R, z) = ClientSign::combine_sigs::<Sha512>(
+            &client_share.joint_public().compress(),
+            &client_share.peer_public().compress(),
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+
+        // the private key share is kept out of debug output
+        let debug = format!("{client_share:?}");
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(&format!("{:?}", p_client)));
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_key_share_bytes() {
+        let (p_client, _, P_client, P_server, P_joint) = run_dkg();
+        let key_share = KeyShare::new(DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_server.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+
+        let bytes = key_share.to_bytes();
+        assert_eq!(bytes.len(), KeyShare::LENGTH);
+        assert_eq!(KeyShare::from_bytes(&bytes).unwrap(), key_share);
+
+        assert_eq!(
+            KeyShare::from_bytes(&bytes[1..]),
+            Err(KeyShareError::Malformed)
+        );
+
+        let mut unknown_version = bytes.to_vec();
+        unknown_version[0] = 2;
+        assert_eq!(
+            KeyShare::from_bytes(&unknown_version),
+            Err(KeyShareError::UnsupportedVersion(2)),
+        );
+
+        // a flipped bit in the private key share no longer matches the public key share
+        let mut corrupted_share = bytes.to_vec();
+        corrupted_share[1] ^= 1;
+        assert_eq!(
+            KeyShare::from_bytes(&corrupted_share),
+            Err(KeyShareError::KeyMismatch),
+        );
+
+        // the joint public key must be the sum of the public key shares
+        let mut swapped_keys = bytes.to_vec();
+        swapped_keys[65..97].copy_from_slice(P_joint.as_bytes());
+        swapped_keys[97..].copy_from_slice(P_server.as_bytes());
+        assert_eq!(
+            KeyShare::from_bytes(&swapped_keys),
+            Err(KeyShareError::KeyMismatch),
+        );
+
+        // a public key with y = 2, which has no x-coordinate on the curve
+        let mut off_curve = bytes.to_vec();
+        off_curve[65..97].copy_from_slice(&[0; 32]);
+        off_curve[65] = 2;
+        assert_eq!(
+            KeyShare::from_bytes(&off_curve),
+            Err(KeyShareError::Decompression),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_second_round_rejects_identity_commitment() {
+        let (p_client, p_server, _, _, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+        let identity = EdwardsPoint::identity().compress();
+
+        for bad_server_message_1 in [
+            SignServerRound1 {
+                D_server: identity,
+                ..server_sign_message_1
+            },
+            SignServerRound1 {
+                E_server: identity,
+                ..server_sign_message_1
+            },
+        ] {
+            assert_eq!(
+                ClientSign::second_round::<Sha512>(
+                    &p_client,
+                    &P_joint,
+                    message,
+                    &d_client,
+                    &e_client,
+                    &client_sign_message_1,
+                    &bad_server_message_1,
+                ),
+                Err(SignError::InvalidCommitment),
+            );
+        }
+
+        for bad_client_message_1 in [
+            SignClientRound1 {
+                D_client: identity,
+                ..client_sign_message_1
+            },
+            SignClientRound1 {
+                E_client: identity,
+                ..client_sign_message_1
+            },
+        ] {
+            assert_eq!(
+                ServerSign::second_round::<Sha512>(
+                    &p_server,
+                    &P_joint,
+                    message,
+                    &d_server,
+                    &e_server,
+                    &bad_client_message_1,
+                    &server_sign_message_1,
+                ),
+                Err(SignError::InvalidCommitment),
+            );
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_finalize_first_round_rejects_weak_commitments() {
+        let (_, _, _, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let (_, _, _, _, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+
+        let torsion = EIGHT_TORSION[1];
+        let identity = EdwardsPoint::identity().compress();
+        let C0 = client_dkg_message_1.C0.decompress().unwrap();
+        let S0 = server_dkg_message_1.S0.decompress().unwrap();
+
+        for bad_client_message_1 in [
+            DkgClientRound1 {
+                C0: (C0 + torsion).compress(),
+                ..client_dkg_message_1
+            },
+            DkgClientRound1 {
+                R: torsion.compress(),
+                ..client_dkg_message_1
+            },
+            DkgClientRound1 {
+                R: identity,
+                ..client_dkg_message_1
+            },
+        ] {
+            assert_eq!(
+                ServerDkg::finalize_first_round::<Sha512>(&bad_client_message_1),
+                Err(DkgError::WeakCommitment {
+                    party: Party::Client
+                }),
+            );
+        }
+
+        for bad_server_message_1 in [
+            DkgServerRound1 {
+                S0: (S0 + torsion).compress(),
+                ..server_dkg_message_1
+            },
+            DkgServerRound1 {
+                R: torsion.compress(),
+                ..server_dkg_message_1
+            },
+            DkgServerRound1 {
+                S0: identity,
+                ..server_dkg_message_1
+            },
+        ] {
+            assert_eq!(
+                ClientDkg::finalize_first_round::<Sha512>(&bad_server_message_1),
+                Err(DkgError::WeakCommitment {
+                    party: Party::Server
+                }),
+            );
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_hedged_nonces() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        // an RNG that repeats its output across sessions
+        let weak_rng = || ChaCha20Rng::from_seed([0u8; 32]);
+
+        // the same randomness with another message or another share gives different nonces
+        let (d_1, e_1, _) =
+            ClientSign::first_round_hedged::<Sha512>(&mut weak_rng(), &p_client, message);
+        let (d_2, e_2, _) =
+            ClientSign::first_round_hedged::<Sha512>(&mut weak_rng(), &p_client, b"other message");
+        let (d_3, e_3, _) =
+            ServerSign::first_round_hedged::<Sha512>(&mut weak_rng(), &p_server, message);
+        assert_ne!(d_1, e_1);
+        assert_ne!((d_1, e_1), (d_2, e_2));
+        assert_ne!((d_1, e_1), (d_3, e_3));
+
+        // fresh randomness gives fresh nonces for the same message
+        let (d_4, e_4, _) =
+            ClientSign::first_round_hedged::<Sha512>(&mut OsRng, &p_client, message);
+        assert_ne!((d_1, e_1), (d_4, e_4));
+
+        // hedged nonces sign like random ones
+        let (d_client, e_client, client_sign_message_1) =
+            ClientSign::first_round_hedged::<Sha512>(&mut OsRng, &p_client, message);
+        let (d_server, e_server, server_sign_message_1) =
+            ServerSign::first_round_hedged::<Sha512>(&mut OsRng, &p_server, message);
+
+        let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round::<Sha512>(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        let (R, z) = ClientSign::combine_sigs::<Sha512>(
+            &P_joint,
+            &P_server,
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_refresh() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let client_share = KeyShare::new(DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_server.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        let server_share = KeyShare::new(DkgOutput {
+            private_share: p_server,
+            own_public: P_server.decompress().unwrap(),
+            peer_public: P_client.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+
+        let (client_delta, client_refresh_message) = start_refresh();
+        let (server_delta, server_refresh_message) = start_refresh();
+
+        let new_client_share =
+            finalize_refresh(&client_share, &client_delta, &server_refresh_message).unwrap();
+        let new_server_share =
+            finalize_refresh(&server_share, &server_delta, &client_refresh_message).unwrap();
+
+        // the shares rotate while the joint public key stays the same
+        assert_ne!(new_client_share.private_share(), &p_client);
+        assert_ne!(new_server_share.private_share(), &p_server);
+        assert_eq!(new_client_share.joint_public().compress(), P_joint);
+        assert_eq!(
+            new_client_share.own_public(),
+            new_server_share.peer_public()
+        );
+        assert_eq!(
+            new_server_share.own_public(),
+            new_client_share.peer_public()
+        );
+
+        // the refreshed shares sign under the old joint public key
+        let message = b"sample message";
+        let (R, z) = run_sign(
+            new_client_share.private_share(),
+            new_server_share.private_share(),
+            &new_server_share.own_public().compress(),
+            &P_joint,
+            message,
+        );
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+
+        // an old share does not combine with a refreshed one
+        verify_reconstructed_secret(&(p_client + new_server_share.private_share()), &P_joint)
+            .unwrap_err();
+
+        // a key share that is inconsistent with its joint public key is not refreshed
+        let inconsistent_share = KeyShare::new(DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_client.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        assert_eq!(
+            finalize_refresh(&inconsistent_share, &client_delta, &server_refresh_message),
+            Err(DkgError::KeyMismatch),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_dkg_commit_then_reveal() {
+        let (_, _, _, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let (_, _, _, _, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+
+        // the commitments are exchanged first, and the round 1 messages only after both arrived
+        let client_commitment = client_dkg_message_1.commitment::<Sha512>();
+        let server_commitment = server_dkg_message_1.commitment::<Sha512>();
+
+        ServerDkg::finalize_first_round_committed::<Sha512>(
+            &client_commitment,
+            &client_dkg_message_1,
+        )
+        .unwrap();
+        ClientDkg::finalize_first_round_committed::<Sha512>(
+            &server_commitment,
+            &server_dkg_message_1,
+        )
+        .unwrap();
+
+        // a round 1 message chosen after the commitment is rejected
+        let (_, _, _, _, other_client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        assert_eq!(
+            ServerDkg::finalize_first_round_committed::<Sha512>(
+                &client_commitment,
+                &other_client_dkg_message_1,
+            ),
+            Err(DkgError::CommitmentMismatch {
+                party: Party::Client
+            }),
+        );
+        let (_, _, _, _, other_server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+        assert_eq!(
+            ClientDkg::finalize_first_round_committed::<Sha512>(
+                &server_commitment,
+                &other_server_dkg_message_1,
+            ),
+            Err(DkgError::CommitmentMismatch {
+                party: Party::Server
+            }),
+        );
+
+        // the revealed messages can carry proofs of knowledge bound to a session ID
+        let session_id = b"session 1";
+        let (_, _, _, _, client_dkg_message_1) =
+            ClientDkg::start_first_round_with_session_id::<Sha512>(&mut OsRng, session_id);
+        let (_, _, _, _, server_dkg_message_1) =
+            ServerDkg::start_first_round_with_session_id::<Sha512>(&mut OsRng, session_id);
+        let client_commitment = client_dkg_message_1.commitment::<Sha512>();
+        let server_commitment = server_dkg_message_1.commitment::<Sha512>();
+
+        ServerDkg::finalize_first_round_committed_with_session_id::<Sha512>(
+            &client_commitment,
+            &client_dkg_message_1,
+            session_id,
+        )
+        .unwrap();
+        ClientDkg::finalize_first_round_committed_with_session_id::<Sha512>(
+            &server_commitment,
+            &server_dkg_message_1,
+            session_id,
+        )
+        .unwrap();
+        assert_eq!(
+            ClientDkg::finalize_first_round_committed::<Sha512>(
+                &server_commitment,
+                &server_dkg_message_1,
+            ),
+            Err(DkgError::ProofOfKnowledge {
+                party: Party::Server
+            }),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_two_party_sign() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let client_share = KeyShare::new(DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_server.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        let server_share = KeyShare::new(DkgOutput {
+            private_share: p_server,
+            own_public: P_server.decompress().unwrap(),
+            peer_public: P_client.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        let message = b"sample message";
+
+        let (R, z) = two_party_sign::<Sha512>(&client_share, &server_share, message).unwrap();
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+
+        // shares of different keys do not sign together
+        let (_, other_p_server, other_P_client, other_P_server, other_P_joint) = run_dkg();
+        let other_server_share = KeyShare::new(DkgOutput {
+            private_share: other_p_server,
+            own_public: other_P_server.decompress().unwrap(),
+            peer_public: other_P_client.decompress().unwrap(),
+            joint_public: other_P_joint.decompress().unwrap(),
+        });
+        assert_eq!(
+            two_party_sign::<Sha512>(&client_share, &other_server_share, message),
+            Err(SignError::PartialSignatureVerification {
+                party: Party::Server,
+            }),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_sign_session_states() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let client_share = KeyShare::new(DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_server.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        let server_share = KeyShare::new(DkgOutput {
+            private_share: p_server,
+            own_public: P_server.decompress().unwrap(),
+            peer_public: P_client.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        let message = b"sample message";
+
+        let client_state = ClientSignRound1State::first_round();
+        let server_state = ServerSignRound1State::first_round();
+        let client_sign_message_1 = *client_state.message();
+        let server_sign_message_1 = *server_state.message();
+
+        let (client_state, client_sign_message_2) = client_state
+            .second_round::<Sha512>(&client_share, message, &server_sign_message_1)
+            .unwrap();
+        let (server_state, server_sign_message_2) = server_state
+            .second_round::<Sha512>(&server_share, message, &client_sign_message_1)
+            .unwrap();
+
+        let (R, z) = client_state
+            .combine_sigs::<Sha512>(&server_sign_message_2)
+            .unwrap();
+        assert_eq!(
+            server_state
+                .combine_sigs::<Sha512>(&client_sign_message_2)
+                .unwrap(),
+            (R, z),
+        );
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+
+        // aborting a session wipes its nonces
+        let mut client_state = ClientSignRound1State::first_round();
+        client_state.zeroize();
+        assert_eq!(client_state.d_client, Scalar::ZERO);
+        assert_eq!(client_state.e_client, Scalar::ZERO);
+        let mut server_state = ServerSignRound1State::first_round();
+        server_state.zeroize();
+        assert_eq!(server_state.d_server, Scalar::ZERO);
+        assert_eq!(server_state.e_server, Scalar::ZERO);
+        ClientSignRound1State::first_round().abort();
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_frost_error() {
+        // a workflow that chains the key generation into signing propagates a single error type
+        fn dkg_then_sign(client_message: &DkgClientRound1) -> Result<(), FrostError> {
+            ServerDkg::finalize_first_round::<Sha512>(client_message)?;
+            Err(SignError::SignatureVerification)?
+        }
+
+        let (_, _, _, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        assert_eq!(
+            dkg_then_sign(&client_dkg_message_1),
+            Err(FrostError::Sign(SignError::SignatureVerification)),
+        );
+
+        let bad_client_dkg_message_1 = DkgClientRound1 {
+            mu: client_dkg_message_1.mu + Scalar::ONE,
+            ..client_dkg_message_1
+        };
+        let error = dkg_then_sign(&bad_client_dkg_message_1).unwrap_err();
+        assert_eq!(
+            error,
+            FrostError::Dkg(DkgError::ProofOfKnowledge {
+                party: Party::Client
+            }),
+        );
+        assert_eq!(
+            error.to_string(),
+            "client proof of knowledge failed to verify"
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_message_display_round_trip() {
+        let (c0, c1, _, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let (s0, s1, _, _, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+        let (_, client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
+        let (_, server_dkg_message_2) = ServerDkg::start_second_round(&s0, &s1);
+
+        assert_eq!(
+            client_dkg_message_1.to_string().parse(),
+            Ok(client_dkg_message_1)
+        );
+        assert_eq!(
+            server_dkg_message_1.to_string().parse(),
+            Ok(server_dkg_message_1)
+        );
+        assert_eq!(
+            client_dkg_message_2.to_string().parse(),
+            Ok(client_dkg_message_2)
+        );
+        assert_eq!(
+            server_dkg_message_2.to_string().parse(),
+            Ok(server_dkg_message_2)
+        );
+
+        // every combination of the optional fields of the signing round 1 messages
+        let (_, _, client_sign_message_1) = ClientSign::first_round();
+        let (_, _, server_sign_message_1) =
+            ServerSign::first_round_with_session(&mut OsRng, [7u8; 32]);
+        for (session_nonce, timestamp) in [
+            (None, None),
+            (Some([7u8; 32]), None),
+            (None, Some(1_700_000_000)),
+            (Some([7u8; 32]), Some(1_700_000_000)),
+        ] {
+            let client_sign_message_1 = SignClientRound1 {
+                session_nonce,
+                timestamp,
+                ..client_sign_message_1
+            };
+            assert_eq!(
+                client_sign_message_1.to_string().parse(),
+                Ok(client_sign_message_1)
+            );
+            let server_sign_message_1 = SignServerRound1 {
+                session_nonce,
+                timestamp,
+                ..server_sign_message_1
+            };
+            assert_eq!(
+                server_sign_message_1.to_string().parse(),
+                Ok(server_sign_message_1)
+            );
+        }
+
+        let client_sign_message_2 = SignClientRound2 {
+            z_client: Scalar::random(&mut OsRng),
+        };
+        let server_sign_message_2 = SignServerRound2 {
+            z_server: Scalar::random(&mut OsRng),
+        };
+        assert_eq!(
+            client_sign_message_2.to_string().parse(),
+            Ok(client_sign_message_2)
+        );
+        assert_eq!(
+            server_sign_message_2.to_string().parse(),
+            Ok(server_sign_message_2)
+        );
+
+        // truncated or extended strings, bad base64, and non-canonical scalars are rejected
+        let encoded = client_dkg_message_1.to_string();
+        assert_eq!(
+            encoded[1..].parse::<DkgClientRound1>(),
+            Err(DkgError::MalformedMessage)
+        );
+        assert_eq!(
+            format!("{encoded}A").parse::<DkgClientRound1>(),
+            Err(DkgError::MalformedMessage)
+        );
+        assert_eq!(
+            encoded.replacen('=', "A", 1).parse::<DkgClientRound1>(),
+            Err(DkgError::MalformedMessage)
+        );
+        assert_eq!(
+            BASE64_STANDARD
+                .encode([0xff; 32])
+                .parse::<SignClientRound2>(),
+            Err(SignError::NonCanonicalScalar)
+        );
+        assert_eq!(
+            "é".repeat(44).parse::<SignServerRound1>(),
+            Err(SignError::MalformedMessage)
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_debug_redacts_secrets() {
+        let (c0, c1, _, _, _) = ClientDkg::start_first_round::<Sha512>();
+        let (s0, s1, _, _, _) = ServerDkg::start_first_round::<Sha512>();
+        let (_, client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
+        let (_, server_dkg_message_2) = ServerDkg::start_second_round(&s0, &s1);
+        let (delta, refresh_message) = start_refresh();
+        let (p_client, _, P_client, P_server, P_joint) = run_dkg();
+        let dkg_output = DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_server.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        };
+
+        for (debug, secret) in [
+            (
+                format!("{client_dkg_message_2:?}"),
+                client_dkg_message_2.c_server,
+            ),
+            (
+                format!("{server_dkg_message_2:?}"),
+                server_dkg_message_2.s_client,
+            ),
+            (format!("{refresh_message:?}"), delta),
+            (format!("{dkg_output:?}"), p_client),
+        ] {
+            assert!(debug.contains("<redacted>"));
+            assert!(!debug.contains(&format!("{secret:?}")));
+        }
+
+        // the public points stay visible
+        assert!(format!("{dkg_output:?}").contains(&format!("{:?}", dkg_output.joint_public)));
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_prehash_reader() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+
+        // a message that spans many chunks and does not end on a chunk boundary
+        let message: Vec<u8> = (0..1_000_003u32).map(|i| (i % 251) as u8).collect();
+        let digest = prehash_reader(message.as_slice()).unwrap();
+        assert_eq!(digest, prehash(&message));
+
+        let variant = SigningVariant::Ed25519ph { context: b"" };
+        let (R, z) =
+            run_sign_with_variant(variant, &p_client, &p_server, &P_server, &P_joint, &digest);
+        verify_signature_with_variant::<Sha512>(variant, &P_joint, &digest, &R, &z).unwrap();
+
+        struct FailingReader;
+        impl std::io::Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+        }
+        assert_eq!(
+            prehash_reader(FailingReader),
+            Err(SignError::MessageRead(std::io::ErrorKind::BrokenPipe)),
+        );
+
+        // the limit is inclusive, and an endless stream is aborted once it crosses it
+        assert_eq!(
+            prehash_reader_with_limit(message.as_slice(), 1_000_003).unwrap(),
+            digest,
+        );
+        assert_eq!(
+            prehash_reader_with_limit(message.as_slice(), 1_000_002),
+            Err(SignError::MessageTooLarge),
+        );
+        assert_eq!(
+            prehash_reader_with_limit(std::io::repeat(7), 1 << 20),
+            Err(SignError::MessageTooLarge),
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_combine_sigs_rejects_weak_public_keys() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+        let (_, client_sign_message_2) = ClientSign::second_round::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round::<Sha512>(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        // a server key share shifted by a point of order 8, and the identity as the joint key
+        let P_server_torsion = (P_server.decompress().unwrap() + EIGHT_TORSION[1]).compress();
+        let P_identity = EdwardsPoint::identity().compress();
+        for (P_joint, P_server) in [(&P_joint, &P_server_torsion), (&P_identity, &P_server)] {
+            assert_eq!(
+                ClientSign::combine_sigs::<Sha512>(
+                    P_joint,
+                    P_server,
+                    message,
+                    &client_sign_message_1,
+                    &client_sign_message_2,
+                    &server_sign_message_1,
+                    &server_sign_message_2,
+                ),
+                Err(SignError::WeakPublicKey),
+            );
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_known_answer_signing() {
+        let scalar = |n: u64| Scalar::from(n);
+        let (c0, c1, s0, s1) = (scalar(1), scalar(2), scalar(3), scalar(4));
+        let (d_client, e_client, d_server, e_server) = (scalar(5), scalar(6), scalar(7), scalar(8));
+
+        // the second round of the DKG only reads the commitments of the round 1 messages
+        let C0 = EdwardsPoint::mul_base(&c0);
+        let C1 = EdwardsPoint::mul_base(&c1);
+        let S0 = EdwardsPoint::mul_base(&s0);
+        let S1 = EdwardsPoint::mul_base(&s1);
+        let client_dkg_message_1 = DkgClientRound1 {
+            C0: C0.compress(),
+            C1: C1.compress(),
+            R: CompressedEdwardsY::default(),
+            mu: Scalar::ZERO,
+        };
+        let server_dkg_message_1 = DkgServerRound1 {
+            S0: S0.compress(),
+            S1: S1.compress(),
+            R: CompressedEdwardsY::default(),
+            mu: Scalar::ZERO,
+        };
+        let (c_client, client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
+        let (s_server, server_dkg_message_2) = ServerDkg::start_second_round(&s0, &s1);
+        let client_output = ClientDkg::finalize_second_round(
+            &c_client,
+            &C0,
+            &C1,
+            &server_dkg_message_1,
+            &server_dkg_message_2,
+        )
+        .unwrap();
+        let server_output = ServerDkg::finalize_second_round(
+            &s_server,
+            &S0,
+            &S1,
+            &client_dkg_message_1,
+            &client_dkg_message_2,
+        )
+        .unwrap();
+        let P_joint = client_output.joint_public.compress();
+        let P_server = client_output.peer_public.compress();
+
+        // the private key shares are `c0 + c1 + s0 + s1` and `c0 - c1 + s0 - s1`
+        assert_eq!(P_joint, EdwardsPoint::mul_base(&scalar(8)).compress());
+
+        let client_sign_message_1 = SignClientRound1 {
+            D_client: EdwardsPoint::mul_base(&d_client).compress(),
+            E_client: EdwardsPoint::mul_base(&e_client).compress(),
+            session_nonce: None,
+            timestamp: None,
+        };
+        let server_sign_message_1 = SignServerRound1 {
+            D_server: EdwardsPoint::mul_base(&d_server).compress(),
+            E_server: EdwardsPoint::mul_base(&e_server).compress(),
+            session_nonce: None,
+            timestamp: None,
+        };
+
+        // the expected `R_joint` and `z_joint` for each message
+        let vectors: [(&[u8], [u8; 32], [u8; 32]); 2] = [
+            (
+                b"",
+                [
+                    0x97, 0xfc, 0xf3, 0x52, 0xb7, 0x64, 0x6a, 0xa8, 0xdb, 0x0c, 0x8e, 0x24, 0x7d,
+                    0xa3, 0xe7, 0x5e, 0xa7, 0x50, 0xe1, 0x89, 0x34, 0x0a, 0xed, 0x9f, 0x86, 0x9b,
+                    0xca, 0xd3, 0x58, 0x85, 0x29, 0x64,
+                ],
+                [
+                    0x32, 0x17, 0x9b, 0x7c, 0x2b, 0xf2, 0x9d, 0xe3, 0xfb, 0xa4, 0xa7, 0xe4, 0xed,
+                    0xe0, 0x53, 0xf3, 0x6d, 0x25, 0xdd, 0xdc, 0xa2, 0x9c, 0x59, 0x4c, 0xfe, 0x54,
+                    0x35, 0x2b, 0x83, 0xb6, 0x13, 0x0e,
+                ],
+            ),
+            (
+                b"sample message",
+                [
+                    0xb5, 0xc1, 0xe3, 0x9b, 0x2c, 0x2c, 0x07, 0xb7, 0x69, 0x8b, 0xb2, 0x12, 0xf7,
+                    0xd8, 0x26, 0x38, 0x7b, 0x78, 0x02, 0xa6, 0x28, 0xb7, 0xfd, 0xa1, 0x2a, 0xe2,
+                    0xa6, 0x88, 0x5a, 0x6a, 0x3f, 0x22,
+                ],
+                [
+                    0x87, 0x9a, 0x1a, 0xc6, 0x04, 0xe3, 0x03, 0xe6, 0x6b, 0x93, 0xc9, 0x21, 0xa5,
+                    0xce, 0x7a, 0x86, 0x60, 0x27, 0x5d, 0x4a, 0xb9, 0x21, 0x4d, 0xe6, 0x2a, 0x0d,
+                    0x0e, 0x33, 0x9b, 0x2e, 0x86, 0x04,
+                ],
+            ),
+        ];
+
+        for (message, expected_R, expected_z) in vectors {
+            let client_prepared = ClientSign::prepare_second_round::<Sha512>(
+                &P_joint,
+                message,
+                &client_sign_message_1,
+                &server_sign_message_1,
+            )
+            .unwrap();
+            let client_sign_message_2 = ClientSign::finish_with_secret::<Sha512>(
+                &client_prepared,
+                message,
+                &client_output.private_share,
+                &d_client,
+                &e_client,
+            )
+            .unwrap();
+            let server_prepared = ServerSign::prepare_second_round::<Sha512>(
+                &P_joint,
+                message,
+                &client_sign_message_1,
+                &server_sign_message_1,
+            )
+            .unwrap();
+            let server_sign_message_2 = ServerSign::finish_with_secret::<Sha512>(
+                &server_prepared,
+                message,
+                &server_output.private_share,
+                &d_server,
+                &e_server,
+            )
+            .unwrap();
+
+            let (R, z) = ClientSign::combine_sigs::<Sha512>(
+                &P_joint,
+                &P_server,
+                message,
+                &client_sign_message_1,
+                &client_sign_message_2,
+                &server_sign_message_1,
+                &server_sign_message_2,
+            )
+            .unwrap();
+            assert_eq!(R.to_bytes(), expected_R);
+            assert_eq!(z.to_bytes(), expected_z);
+            verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_sha512_wrappers() {
+        let (_, _, _, _, client_dkg_message_1) = ClientDkg::start_first_round_sha512();
+        let (_, _, _, _, server_dkg_message_1) = ServerDkg::start_first_round_sha512();
+        ClientDkg::finalize_first_round_sha512(&server_dkg_message_1).unwrap();
+        ServerDkg::finalize_first_round_sha512(&client_dkg_message_1).unwrap();
+
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+        let (_, client_sign_message_2) = ClientSign::second_round_sha512(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round_sha512(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        let (R, z) = ClientSign::combine_sigs_sha512(
+            &P_joint,
+            &P_server,
+            message,
+            &client_sign_message_1,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+        verify_signature_sha512(&P_joint, message, &R, &z).unwrap();
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_run_over_transport() {
+        use std::{
+            cell::RefCell,
+            collections::VecDeque,
+            future::{self, Future},
+            pin::pin,
+            rc::Rc,
+            task::{Context, Poll, Waker},
+        };
+
+        use crate::transport::{run_dkg, run_sign, AsyncTransport, TransportError};
+
+        type Queue = Rc<RefCell<VecDeque<Vec<u8>>>>;
+
+        struct MemoryTransport {
+            outbox: Queue,
+            inbox: Queue,
+        }
+
+        impl AsyncTransport for MemoryTransport {
+            type Error = &'static str;
+
+            fn send(&mut self, message: Vec<u8>) -> impl Future<Output = Result<(), Self::Error>> {
+                self.outbox.borrow_mut().push_back(message);
+                future::ready(Ok(()))
+            }
+
+            fn recv(&mut self) -> impl Future<Output = Result<Vec<u8>, Self::Error>> {
+                future::poll_fn(|_| match self.inbox.borrow_mut().pop_front() {
+                    Some(message) => Poll::Ready(Ok(message)),
+                    None => Poll::Pending,
+                })
+            }
+        }
+
+        fn connect() -> (MemoryTransport, MemoryTransport) {
+            let (a, b) = (Queue::default(), Queue::default());
+            (
+                MemoryTransport {
+                    outbox: a.clone(),
+                    inbox: b.clone(),
+                },
+                MemoryTransport {
+                    outbox: b,
+                    inbox: a,
+                },
+            )
+        }
+
+        // polls both parties in turn until both are done
+        fn join<A: Future, B: Future>(a: A, b: B) -> (A::Output, B::Output) {
+            let (mut a, mut b) = (pin!(a), pin!(b));
+            let mut cx = Context::from_waker(Waker::noop());
+            let (mut a_output, mut b_output) = (None, None);
+            for _ in 0..100 {
+                if a_output.is_none() {
+                    if let Poll::Ready(output) = a.as_mut().poll(&mut cx) {
+                        a_output = Some(output);
+                    }
+                }
+                if b_output.is_none() {
+                    if let Poll::Ready(output) = b.as_mut().poll(&mut cx) {
+                        b_output = Some(output);
+                    }
+                }
+                if let (Some(_), Some(_)) = (&a_output, &b_output) {
+                    return (a_output.unwrap(), b_output.unwrap());
+                }
+            }
+            panic!("the parties deadlocked");
+        }
+
+        let (mut client_transport, mut server_transport) = connect();
+        let (client_share, server_share) = join(
+            run_dkg::<Sha512, _>(Party::Client, &mut client_transport),
+            run_dkg::<Sha512, _>(Party::Server, &mut server_transport),
+        );
+        let (client_share, server_share) = (client_share.unwrap(), server_share.unwrap());
+        assert_eq!(client_share.joint_public(), server_share.joint_public());
+        let P_joint = client_share.joint_public().compress();
+
+        let message = b"sample message";
+        let (client_signature, server_signature) = join(
+            run_sign::<Sha512, _>(Party::Client, &client_share, message, &mut client_transport),
+            run_sign::<Sha512, _>(Party::Server, &server_share, message, &mut server_transport),
+        );
+        let (R, z) = client_signature.unwrap();
+        assert_eq!(server_signature.unwrap(), (R, z));
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+
+        // a malformed message from the peer fails the protocol, not the transport
+        let (mut client_transport, server_transport) = connect();
+        server_transport.outbox.borrow_mut().push_back(vec![0u8; 3]);
+        let (client_signature, _) = join(
+            run_sign::<Sha512, _>(Party::Client, &client_share, message, &mut client_transport),
+            future::ready(()),
+        );
+        assert_eq!(
+            client_signature,
+            Err(TransportError::Protocol(FrostError::Sign(
+                SignError::MalformedMessage
+            ))),
+        );
+    }
+
+    #[test]
+    pub fn test_round1_messages_as_map_keys() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let (_, _, first) = ClientSign::first_round();
+        let (_, _, second) = ClientSign::first_round();
+
+        let hashed: HashSet<_> = [first, second, first].into_iter().collect();
+        assert_eq!(hashed.len(), 2);
+
+        // the order follows the byte encodings
+        let ordered: BTreeSet<_> = [first, second, first].into_iter().collect();
+        assert_eq!(ordered.len(), 2);
+        assert_eq!(first.cmp(&second), first.to_bytes().cmp(&second.to_bytes()));
+
+        let (_, _, _, _, client_dkg_message_1) = ClientDkg::start_first_round::<Sha512>();
+        let (_, _, _, _, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+        let (_, _, server_sign_message_1) = ServerSign::first_round();
+        assert!(HashSet::from([client_dkg_message_1]).contains(&client_dkg_message_1));
+        assert!(BTreeSet::from([server_dkg_message_1]).contains(&server_dkg_message_1));
+        assert!(BTreeSet::from([server_sign_message_1]).contains(&server_sign_message_1));
+        assert!(HashSet::from([client_dkg_message_1.commitment::<Sha512>()])
+            .contains(&client_dkg_message_1.commitment::<Sha512>()));
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_second_round_with_commitment() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        // the client runs two sessions at once and records each session's commitment
+        let (d_a, e_a, client_message_a) = ClientSign::first_round();
+        let (_, _, client_message_b) = ClientSign::first_round();
+        let commitment_a = client_message_a.commitment_hash::<Sha512>();
+        assert_ne!(commitment_a, client_message_b.commitment_hash::<Sha512>());
+
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        // the round 1 message of session b is not signed with the nonces of session a
+        assert_eq!(
+            ClientSign::second_round_with_commitment::<Sha512>(
+                &p_client,
+                &P_joint,
+                message,
+                &d_a,
+                &e_a,
+                &client_message_b,
+                &server_sign_message_1,
+                &commitment_a,
+            ),
+            Err(SignError::CommitmentMismatch),
+        );
+
+        let (_, client_sign_message_2) = ClientSign::second_round_with_commitment::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_a,
+            &e_a,
+            &client_message_a,
+            &server_sign_message_1,
+            &commitment_a,
+        )
+        .unwrap();
+        let (_, server_sign_message_2) = ServerSign::second_round_with_commitment::<Sha512>(
+            &p_server,
+            &P_joint,
+            message,
+            &d_server,
+            &e_server,
+            &client_message_a,
+            &server_sign_message_1,
+            &server_sign_message_1.commitment_hash::<Sha512>(),
+        )
+        .unwrap();
+        let (R, z) = ClientSign::combine_sigs::<Sha512>(
+            &P_joint,
+            &P_server,
+            message,
+            &client_message_a,
+            &client_sign_message_2,
+            &server_sign_message_1,
+            &server_sign_message_2,
+        )
+        .unwrap();
+        verify_signature::<Sha512>(&P_joint, message, &R, &z).unwrap();
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_key_aggregation_mode() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let client_output = DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_server.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        }
+        .with_key_aggregation::<Sha512>(Party::Client);
+        let server_output = DkgOutput {
+            private_share: p_server,
+            own_public: P_server.decompress().unwrap(),
+            peer_public: P_client.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        }
+        .with_key_aggregation::<Sha512>(Party::Server);
+
+        // both parties arrive at the same joint key, which is not the plain sum
+        let (a_client, a_server) = key_aggregation_coefficients::<Sha512>(&P_client, &P_server);
+        let aggregated_P_joint = client_output.joint_public.compress();
+        assert_eq!(aggregated_P_joint, server_output.joint_public.compress());
+        assert_ne!(aggregated_P_joint, P_joint);
+        assert_eq!(
+            client_output.joint_public,
+            P_client.decompress().unwrap() * a_client + P_server.decompress().unwrap() * a_server,
+        );
+
+        // the scaled shares sign under the aggregated key
+        let message = b"sample message";
+        let (R, z) = run_sign(
+            &client_output.private_share,
+            &server_output.private_share,
+            &client_output.peer_public.compress(),
+            &aggregated_P_joint,
+            message,
+        );
+        verify_signature::<Sha512>(&aggregated_P_joint, message, &R, &z).unwrap();
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_binding_factor() {
+        let (_, _, _, _, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (_, _, client_sign_message_1) = ClientSign::first_round();
+        let (_, _, server_sign_message_1) = ServerSign::first_round();
+        let client_sign_message_1 = SignClientRound1 {
+            session_nonce: Some([7u8; 32]),
+            ..client_sign_message_1
+        };
+        let server_sign_message_1 = SignServerRound1 {
+            session_nonce: Some([7u8; 32]),
+            timestamp: Some(1_700_000_000),
+            ..server_sign_message_1
+        };
+        let client_prepared = ClientSign::prepare_second_round::<Sha512>(
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        let server_prepared = ServerSign::prepare_second_round::<Sha512>(
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        // the public helper reproduces the binding factors that the rounds use
+        assert_eq!(
+            binding_factor::<Sha512>(
+                b"client",
+                message,
+                &client_sign_message_1.D_client,
+                &client_sign_message_1.E_client,
+                client_sign_message_1.session_nonce,
+                client_sign_message_1.timestamp,
+            ),
+            client_prepared.rho,
+        );
+        assert_eq!(
+            binding_factor::<Sha512>(
+                b"server",
+                message,
+                &server_sign_message_1.D_server,
+                &server_sign_message_1.E_server,
+                server_sign_message_1.session_nonce,
+                server_sign_message_1.timestamp,
+            ),
+            server_prepared.rho,
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_signing_challenge() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (_, _, client_sign_message_1) = ClientSign::first_round();
+        let (_, _, server_sign_message_1) = ServerSign::first_round();
+        let prepared = ClientSign::prepare_second_round::<Sha512>(
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        assert_eq!(
+            signing_challenge::<Sha512>(&prepared.nonce_commitment(), message, &P_joint),
+            prepared.challenge(),
+        );
+
+        // a signature satisfies `z * G = R + c * P_joint` with the same challenge
+        let (R, z) = run_sign(&p_client, &p_server, &P_server, &P_joint, message);
+        let c = signing_challenge::<Sha512>(&R, message, &P_joint);
+        assert_eq!(
+            EdwardsPoint::mul_base(&z),
+            R.decompress().unwrap() + P_joint.decompress().unwrap() * c,
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_non_canonical_scalars_rejected() {
+        // the group order `l`, the smallest non-canonical encoding
+        let l = group_order_bytes();
+        assert!(bool::from(Scalar::from_canonical_bytes(l).is_none()));
+
+        let (_, _, _, _, server_dkg_message_1) = ServerDkg::start_first_round::<Sha512>();
+        let mut bytes = server_dkg_message_1.to_bytes();
+        bytes[96..].copy_from_slice(&l);
+        assert_eq!(
+            DkgServerRound1::from_bytes(&bytes),
+            Err(DkgError::NonCanonicalScalar)
+        );
+
+        for result in [
+            DkgClientRound2::from_bytes(&l).map(|_| ()),
+            DkgServerRound2::from_bytes(&l).map(|_| ()),
+            RefreshMessage::from_bytes(&l).map(|_| ()),
+        ] {
+            assert_eq!(result, Err(DkgError::NonCanonicalScalar));
+        }
+        for result in [
+            SignClientRound2::from_bytes(&l).map(|_| ()),
+            SignServerRound2::from_bytes(&l).map(|_| ()),
+        ] {
+            assert_eq!(result, Err(SignError::NonCanonicalScalar));
+        }
+
+        // one less than `l` is the largest canonical encoding
+        let mut l_minus_one = l;
+        l_minus_one[0] -= 1;
+        assert_eq!(
+            SignServerRound2::from_bytes(&l_minus_one).map(|message| message.z_server),
+            Ok(-Scalar::ONE)
+        );
+
+        let (p_client, _, P_client, P_server, P_joint) = run_dkg();
+        let key_share = KeyShare::new(DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_server.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        let mut bytes = key_share.to_bytes();
+        bytes[1..33].copy_from_slice(&l);
+        assert_eq!(
+            KeyShare::from_bytes(&bytes),
+            Err(KeyShareError::NonCanonicalScalar)
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_signature_bytes_round_trip() {
+        let (p_client, p_server, _, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+        let (R, z) = run_sign(&p_client, &p_server, &P_server, &P_joint, message);
+
+        let signature = to_signature_bytes(&R, &z);
+        assert_eq!(from_signature_bytes(&signature), Ok((R, z)));
+        verify_external_signature(&P_joint, message, &signature).unwrap();
+
+        // the signature meets the strict checks: canonical `s`, and `R` and `P_joint` free of
+        // small-order components
+        let R_point = R.decompress().unwrap();
+        assert!(!R_point.is_small_order() && R_point.is_torsion_free());
+        assert!(P_joint.decompress().unwrap().is_torsion_free());
+
+        // adding the group order to `s` gives the classic malleated signature, which is rejected
+        let mut malleated = signature;
+        let mut carry = 0u16;
+        let l = group_order_bytes();
+        for i in 0..32 {
+            let sum = malleated[32 + i] as u16 + l[i] as u16 + carry;
+            malleated[32 + i] = sum as u8;
+            carry = sum >> 8;
+        }
+        assert_eq!(carry, 0);
+        assert_eq!(
+            from_signature_bytes(&malleated),
+            Err(SignError::NonCanonicalScalar)
+        );
+        assert_eq!(
+            verify_external_signature(&P_joint, message, &malleated),
+            Err(SignError::SignatureVerification)
+        );
+    }
+
+    #[cfg(feature = "dangerous-recovery")]
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_recover_joint_secret() {
+        use crate::recovery::{combine_secret_shares, recover_joint_secret};
+        use crate::sign::schnorr_sign;
+
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let client_share = KeyShare::new(DkgOutput {
+            private_share: p_client,
+            own_public: P_client.decompress().unwrap(),
+            peer_public: P_server.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+        let server_share = KeyShare::new(DkgOutput {
+            private_share: p_server,
+            own_public: P_server.decompress().unwrap(),
+            peer_public: P_client.decompress().unwrap(),
+            joint_public: P_joint.decompress().unwrap(),
+        });
+
+        // the distributed key equals the monolithic key of the combined secret
+        let secret = recover_joint_secret(&client_share, &server_share).unwrap();
+        assert_eq!(*secret, combine_secret_shares(&p_client, &p_server));
+        assert_eq!(EdwardsPoint::mul_base(&secret).compress(), P_joint);
+
+        // a signature of the monolithic key verifies as a standard signature under `P_joint`
+        let message = b"sample message";
+        let (R, z) = schnorr_sign::<Sha512>(&secret, &P_joint, message);
+        verify_external_signature(&P_joint, message, &to_signature_bytes(&R, &z)).unwrap();
+
+        // shares of different keys are not combined
+        let (_, other_p_server, other_P_client, other_P_server, other_P_joint) = run_dkg();
+        let other_server_share = KeyShare::new(DkgOutput {
+            private_share: other_p_server,
+            own_public: other_P_server.decompress().unwrap(),
+            peer_public: other_P_client.decompress().unwrap(),
+            joint_public: other_P_joint.decompress().unwrap(),
+        });
+        assert_eq!(
+            recover_joint_secret(&client_share, &other_server_share).map(|_| ()),
+            Err(DkgError::KeyMismatch)
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_second_round_with_partial() {
+        let (p_client, p_server, P_client, P_server, P_joint) = run_dkg();
+        let message = b"sample message";
+
+        let (d_client, e_client, client_sign_message_1) = ClientSign::first_round();
+        let (d_server, e_server, server_sign_message_1) = ServerSign::first_round();
+
+        let (R, server_sign_message_2, server_partial) =
+            ServerSign::second_round_with_partial::<Sha512>(
+                &p_server,
+                &P_joint,
+                message,
+                &d_server,
+                &e_server,
+                &client_sign_message_1,
+                &server_sign_message_1,
+            )
+            .unwrap();
+        assert_eq!(server_partial.z, server_sign_message_2.z_server);
+
+        // the server's contribution is checked before the client's arrives
+        verify_partial::<Sha512>(
+            &server_partial,
+            &P_server,
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        let (client_R, _, client_partial) = ClientSign::second_round_with_partial::<Sha512>(
+            &p_client,
+            &P_joint,
+            message,
+            &d_client,
+            &e_client,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+        assert_eq!(client_R, R);
+        verify_partial::<Sha512>(
+            &client_partial,
+            &P_client,
+            &P_joint,
+            message,
+            &client_sign_message_1,
+            &server_sign_message_1,
+        )
+        .unwrap();
+
+        let signature = aggregate_partials::<Sha512>(
+            &client_partial,
+            &server_partial,
+            &P_client,
+            &P_server,
+            message,
+        )
+        .unwrap();
+        verify_external_signature(&P_joint, message, &signature).unwrap();
+
+        // a faulty contribution is rejected on arrival
+        let faulty_partial = PartialSignatureMessage {
+            z: server_partial.z + Scalar::ONE,
+            ..server_partial
+        };
+        assert_eq!(
+            verify_partial::<Sha512>(
+                &faulty_partial,
+                &P_server,
+                &P_joint,
+                message,
+                &client_sign_message_1,
+                &server_sign_message_1,
+            ),
+            Err(SignError::PartialSignatureVerification {
+                party: Party::Server
+            })
+        );
+
+        // so is a nonce commitment share that does not match the round 1 messages
+        let z = Scalar::random(&mut OsRng);
+        let c = signing_challenge::<Sha512>(&R.compress(), message, &P_joint);
+        let forged_partial = PartialSignatureMessage {
+            R_party: (EdwardsPoint::mul_base(&z) - P_server.decompress().unwrap() * c).compress(),
+            z,
+            ..server_partial
+        };
+        assert_eq!(
+            verify_partial::<Sha512>(
+                &forged_partial,
+                &P_server,
+                &P_joint,
+                message,
+                &client_sign_message_1,
+                &server_sign_message_1,
+            ),
+            Err(SignError::PartialSignatureVerification {
+                party: Party::Server
+            })
+        );
+
+        // and a contribution checked against a weak key share
+        for weak_P_server in [
+            (P_server.decompress().unwrap() + EIGHT_TORSION[1]).compress(),
+            EdwardsPoint::identity().compress(),
+        ] {
+            assert_eq!(
+                verify_partial::<Sha512>(
+                    &server_partial,
+                    &weak_P_server,
+                    &P_joint,
+                    message,
+                    &client_sign_message_1,
+                    &server_sign_message_1,
+                ),
+                Err(SignError::WeakPublicKey)
+            );
+        }
     }
 }
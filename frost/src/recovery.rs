@@ -0,0 +1,48 @@
+//! Reconstruction of the joint secret key from both key shares
+//!
+//! **Dangerous.** The two-party protocol exists so that the joint secret key never exists in one
+//! place. Reconstructing it puts the full signing power of the joint key into a single process,
+//! and anyone who obtains both shares or the reconstructed secret can sign on their own. Use this
+//! only for disaster recovery, escrow, and tests, on a machine that is trusted with the full key,
+//! and zeroize the secret as soon as it is no longer needed.
+//!
+//! The module is only compiled with the `dangerous-recovery` feature.
+//!
+//! The reconstructed secret is the scalar of the joint public key, not an RFC 8032 seed, so it
+//! cannot be loaded as an `ed25519_dalek::SigningKey`, which hashes a seed into its scalar.
+//! `sign::schnorr_sign` signs with it directly, and the signatures are standard Ed25519 signatures
+//! under the joint public key. With ed25519-dalek, the scalar can be used through
+//! `hazmat::ExpandedSecretKey` together with a fresh random `hash_prefix`.
+
+use curve25519_dalek::{edwards::EdwardsPoint, scalar::Scalar};
+use zeroize::Zeroizing;
+
+use crate::{dkg::DkgError, key_share::KeyShare};
+
+/// Combines the private key shares into the joint secret key `p_client + p_server`
+pub fn combine_secret_shares(p_client: &Scalar, p_server: &Scalar) -> Scalar {
+    p_client + p_server
+}
+
+/// Reconstructs the joint secret key from the key shares of both parties
+///
+/// Fails with `DkgError::KeyMismatch` if the shares belong to different joint keys or the
+/// reconstructed secret is not the secret key of the joint public key.
+pub fn recover_joint_secret(
+    client_share: &KeyShare,
+    server_share: &KeyShare,
+) -> Result<Zeroizing<Scalar>, DkgError> {
+    if client_share.joint_public() != server_share.joint_public() {
+        return Err(DkgError::KeyMismatch);
+    }
+
+    let secret = Zeroizing::new(combine_secret_shares(
+        client_share.private_share(),
+        server_share.private_share(),
+    ));
+    if EdwardsPoint::mul_base(&secret) != *client_share.joint_public() {
+        return Err(DkgError::KeyMismatch);
+    }
+
+    Ok(secret)
+}
@@ -0,0 +1,141 @@
+//! Aggregation of partial signatures by a coordinator that holds no key share
+//!
+//! Each party sends its partial signature together with its own share of the nonce commitment to
+//! the coordinator, which verifies both partial signatures before combining them. The coordinator
+//! does not need to be trusted, since a signature it assembles from bad partials fails to verify.
+
+use curve25519_dalek::{
+    digest::{generic_array::typenum::U64, Digest},
+    edwards::CompressedEdwardsY,
+    scalar::Scalar,
+};
+
+use crate::{
+    sign::{
+        client::*, decompress_public_key, derive_session_id, server::*, to_signature_bytes,
+        verify_partial_with_R, SignError,
+    },
+    Party,
+};
+
+/// A party's partial signature `z` with its share `R_party` of the joint nonce commitment, as sent
+/// to a coordinator
+///
+/// `R_party` is the party's `PreparedSecondRound::party_commitment`.
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PartialSignatureMessage {
+    pub role: Party,
+    pub R_party: CompressedEdwardsY,
+    pub z: Scalar,
+    pub session_id: [u8; 32],
+}
+
+/// Verifies the partial signatures of both parties and combines them into a 64-byte `R || z`
+/// signature under `P_client + P_server`
+///
+/// Fails with `SignError::UnexpectedParty` if a partial signature is not from the expected party,
+/// with `SignError::StaleCommitment` if the partial signatures belong to different sessions, with
+/// `SignError::WeakPublicKey` if a public key share is the identity or has a small-order component,
+/// and with `SignError::PartialSignatureVerification` naming the party whose partial signature does
+/// not verify.
+#[allow(non_snake_case)]
+pub fn aggregate_partials<CtxDigest>(
+    client_partial: &PartialSignatureMessage,
+    server_partial: &PartialSignatureMessage,
+    P_client: &CompressedEdwardsY,
+    P_server: &CompressedEdwardsY,
+    message: &[u8],
+) -> Result<[u8; 64], SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    if client_partial.role != Party::Client || server_partial.role != Party::Server {
+        return Err(SignError::UnexpectedParty);
+    }
+    if client_partial.session_id != server_partial.session_id {
+        return Err(SignError::StaleCommitment);
+    }
+
+    let R_client = client_partial
+        .R_party
+        .decompress()
+        .ok_or(SignError::Decompression)?;
+    let R_server = server_partial
+        .R_party
+        .decompress()
+        .ok_or(SignError::Decompression)?;
+    let R = (R_client + R_server).compress();
+
+    let P_joint = (decompress_public_key(P_client)? + decompress_public_key(P_server)?).compress();
+
+    for (partial, P_party) in [(client_partial, P_client), (server_partial, P_server)] {
+        verify_partial_with_R::<CtxDigest>(
+            partial.role,
+            &R,
+            &partial.R_party,
+            P_party,
+            message,
+            &P_joint,
+            &partial.z,
+        )?;
+    }
+
+    let z = client_partial.z + server_partial.z;
+
+    Ok(to_signature_bytes(&R, &z))
+}
+
+/// Verifies one party's partial signature as soon as it arrives, without waiting for the other
+///
+/// The coordinator recomputes the joint nonce commitment and the party's share of it from the
+/// round 1 messages of both parties, so the check also covers the `R_party` that the party sent.
+/// Fails with `SignError::StaleCommitment` if the partial signature belongs to another session,
+/// with `SignError::WeakPublicKey` if `P_party` is the identity or has a small-order component, and
+/// with `SignError::PartialSignatureVerification` naming the party if it does not verify.
+#[allow(non_snake_case)]
+pub fn verify_partial<CtxDigest>(
+    partial: &PartialSignatureMessage,
+    P_party: &CompressedEdwardsY,
+    P_joint: &CompressedEdwardsY,
+    message: &[u8],
+    client_message: &SignClientRound1,
+    server_message: &SignServerRound1,
+) -> Result<(), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    if partial.session_id != derive_session_id::<CtxDigest>(client_message, server_message) {
+        return Err(SignError::StaleCommitment);
+    }
+
+    let prepared = match partial.role {
+        Party::Client => ClientSign::prepare_second_round::<CtxDigest>(
+            P_joint,
+            message,
+            client_message,
+            server_message,
+        )?,
+        Party::Server => ServerSign::prepare_second_round::<CtxDigest>(
+            P_joint,
+            message,
+            client_message,
+            server_message,
+        )?,
+    };
+    if prepared.party_commitment() != partial.R_party {
+        return Err(SignError::PartialSignatureVerification {
+            party: partial.role,
+        });
+    }
+
+    verify_partial_with_R::<CtxDigest>(
+        partial.role,
+        &prepared.nonce_commitment(),
+        &partial.R_party,
+        P_party,
+        message,
+        P_joint,
+        &partial.z,
+    )
+}
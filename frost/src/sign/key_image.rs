@@ -0,0 +1,68 @@
+//! Key images `I = p_joint * Hp(P_joint)` for double-spend detection, computed jointly from the
+//! key shares
+//!
+//! Each party contributes `p * Hp(P_joint)` for its own share `p`, and the key image is the sum of
+//! the contributions. A contribution is not accompanied by a proof that it was computed with the
+//! party's actual share.
+
+use curve25519_dalek::{
+    digest::{generic_array::typenum::U64, Digest},
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+    traits::{Identity, IsIdentity},
+};
+
+use crate::sign::SignError;
+
+/// The domain separation label of the hash to the curve
+const KEY_IMAGE_LABEL: &[u8] = b"frost key image";
+
+/// Computes a party's contribution `p * Hp(P_joint)` to the key image of `P_joint`
+#[allow(non_snake_case)]
+pub fn partial_key_image<CtxDigest>(p: &Scalar, P_joint: &CompressedEdwardsY) -> CompressedEdwardsY
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    (hash_to_point::<CtxDigest>(P_joint) * p).compress()
+}
+
+/// Combines the parties' contributions into the key image
+pub fn combine_key_image(partials: &[CompressedEdwardsY]) -> Result<CompressedEdwardsY, SignError> {
+    let mut key_image = EdwardsPoint::identity();
+    for partial in partials {
+        key_image += partial.decompress().ok_or(SignError::Decompression)?;
+    }
+
+    Ok(key_image.compress())
+}
+
+/// Hashes the joint public key to a point of the prime-order subgroup whose discrete log is
+/// unknown
+///
+/// Hash outputs are tried as point encodings until one decompresses, and the result is multiplied
+/// by the cofactor. This is not constant time, which is fine since the input is public.
+#[allow(non_snake_case)]
+fn hash_to_point<CtxDigest>(P_joint: &CompressedEdwardsY) -> EdwardsPoint
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let mut counter: u32 = 0;
+    loop {
+        let mut h = CtxDigest::new();
+        h.update(KEY_IMAGE_LABEL);
+        h.update(P_joint.as_bytes());
+        h.update(counter.to_le_bytes());
+        let output = h.finalize();
+
+        let mut candidate = [0u8; 32];
+        candidate.copy_from_slice(&output[..32]);
+        if let Some(point) = CompressedEdwardsY(candidate).decompress() {
+            let point = point.mul_by_cofactor();
+            if !point.is_identity() {
+                return point;
+            }
+        }
+
+        counter += 1;
+    }
+}
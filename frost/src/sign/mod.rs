@@ -1,12 +1,981 @@
+use std::io::{self, Read};
+
+use curve25519_dalek::{
+    digest::{generic_array::typenum::U64, Digest},
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use sha2::Sha512;
+use subtle::ConstantTimeEq;
 use thiserror::Error;
 
+use crate::{
+    key_share::KeyShare,
+    sign::{
+        client::{ClientSign, SignClientRound1},
+        server::{ServerSign, SignServerRound1},
+    },
+    wire::{array_at, base64_segments, point_at, scalar_at},
+    Party,
+};
+
 pub mod client;
+pub mod coordinator;
+pub mod key_image;
+pub mod receipt;
 pub mod server;
 
 #[derive(Error, Clone, Debug, Eq, PartialEq)]
 pub enum SignError {
     #[error("compressed curve point failed to decompress")]
     Decompression,
-    #[error("partial signature failed to verify")]
-    PartialSignatureVerification,
+    #[error("the {party} partial signature failed to verify")]
+    PartialSignatureVerification { party: Party },
+    #[error("signature failed to verify")]
+    SignatureVerification,
+    #[error("failed to read the message: {0}")]
+    MessageRead(io::ErrorKind),
+    #[error("the message differs from the one the signing round was prepared for")]
+    MessageMismatch,
+    #[error("the joint nonce commitment is not in the prime-order subgroup")]
+    WeakCommitment,
+    #[error("a nonce commitment is the identity point")]
+    InvalidCommitment,
+    #[error("the round 1 commitments belong to different signing sessions")]
+    StaleCommitment,
+    #[error("a partial signature is zero")]
+    ZeroPartialSignature,
+    #[error("the round 1 timestamp is missing or outside of the accepted range")]
+    TimestampOutOfRange,
+    #[error("the message was rejected by the signing policy")]
+    PolicyRejected,
+    #[error("the combined signature failed to verify")]
+    AggregateInvalid,
+    #[error("the message is not from the expected party")]
+    UnexpectedParty,
+    #[error("the {party} nonce commitment is not in the prime-order subgroup")]
+    WeakPartyCommitment { party: Party },
+    #[error("the message is malformed")]
+    MalformedMessage,
+    #[error("a scalar in the message is not canonically encoded")]
+    NonCanonicalScalar,
+    #[error("the message exceeds the size limit")]
+    MessageTooLarge,
+    #[error("the context string is longer than 255 bytes")]
+    ContextTooLong,
+    #[error("the context string of Ed25519ctx is empty")]
+    EmptyContext,
+    #[error("a public key is the identity or not in the prime-order subgroup")]
+    WeakPublicKey,
+    #[error("the round 1 message does not match the commitment of the signing session")]
+    CommitmentMismatch,
+}
+
+/// The RFC 8032 variant of Ed25519 that a signature is made under
+///
+/// The variants hash different domain separation prefixes into the challenge, so a signature made
+/// under one variant does not verify under another.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SigningVariant<'a> {
+    /// Plain Ed25519 over the message
+    Ed25519,
+    /// Ed25519ctx, which binds the signature to an application context so that it does not verify
+    /// under another context with the same key
+    ///
+    /// The context must be between 1 and 255 bytes long.
+    Ed25519ctx { context: &'a [u8] },
+    /// Ed25519ph, where the message passed to the signing and verification functions is the
+    /// 64-byte prehash of the actual message computed with `prehash`
+    ///
+    /// The context may be empty and must be at most 255 bytes long.
+    Ed25519ph { context: &'a [u8] },
+}
+
+/// The prefix of the domain separation string `dom2` of RFC 8032
+const DOM2_PREFIX: &[u8] = b"SigEd25519 no Ed25519 collisions";
+
+/// Computes the Ed25519ph prehash `SHA-512(message)` of a message
+pub fn prehash(message: &[u8]) -> [u8; 64] {
+    let mut digest = [0u8; 64];
+    digest.copy_from_slice(&Sha512::digest(message));
+    digest
+}
+
+/// Computes the Ed25519ph prehash of a message read from `reader`, without holding the whole
+/// message in memory
+///
+/// Signing a large message this way means signing the prehash under `SigningVariant::Ed25519ph`.
+/// Both parties compute the same prehash from the same message, so their challenges agree.
+pub fn prehash_reader(reader: impl Read) -> Result<[u8; 64], SignError> {
+    prehash_reader_with_limit(reader, u64::MAX)
+}
+
+/// `prehash_reader` that stops reading once the message exceeds `max_bytes`
+///
+/// A message longer than `max_bytes` fails with `SignError::MessageTooLarge` as soon as the chunk
+/// that crosses the limit is read, so an unbounded `reader` cannot keep the signer hashing forever.
+pub fn prehash_reader_with_limit(reader: impl Read, max_bytes: u64) -> Result<[u8; 64], SignError> {
+    let mut h = Sha512::new();
+    update_from_reader(&mut h, reader, max_bytes)?;
+
+    let mut digest = [0u8; 64];
+    digest.copy_from_slice(&h.finalize());
+    Ok(digest)
+}
+
+/// Computes the Ed25519 challenge `c = H(R || P_joint || message)` of a signature
+///
+/// This is the challenge of `SigningVariant::Ed25519`, which both parties compute in the second
+/// round and the verifiers recompute. It is hashed in the order of RFC 8032.
+#[allow(non_snake_case)]
+pub fn signing_challenge<CtxDigest>(
+    R: &CompressedEdwardsY,
+    message: &[u8],
+    P_joint: &CompressedEdwardsY,
+) -> Scalar
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    finish_challenge(CtxDigest::new(), R, P_joint, message)
+}
+
+/// Hashes the transcript `R || P_joint || message` of the challenge into `h`, which may already
+/// hold a `dom2` prefix, and reduces the digest to the challenge
+#[allow(non_snake_case)]
+fn finish_challenge<CtxDigest>(
+    mut h: CtxDigest,
+    R: &CompressedEdwardsY,
+    P_joint: &CompressedEdwardsY,
+    message: &[u8],
+) -> Scalar
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    h.update(R.as_bytes());
+    h.update(P_joint.as_bytes());
+    h.update(message);
+    Scalar::from_hash(h)
+}
+
+/// Computes the challenge `c = H(dom2 || R || P_joint || message)` of `variant`, where `dom2` is
+/// empty for plain Ed25519
+#[allow(non_snake_case)]
+pub(crate) fn variant_challenge<CtxDigest>(
+    variant: SigningVariant,
+    R: &CompressedEdwardsY,
+    P_joint: &CompressedEdwardsY,
+    message: &[u8],
+) -> Result<Scalar, SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let mut h = CtxDigest::new();
+    match variant {
+        SigningVariant::Ed25519 => (),
+        SigningVariant::Ed25519ctx { context } => {
+            if context.is_empty() {
+                return Err(SignError::EmptyContext);
+            }
+            update_dom2(&mut h, 0, context)?;
+        }
+        SigningVariant::Ed25519ph { context } => update_dom2(&mut h, 1, context)?,
+    }
+    Ok(finish_challenge(h, R, P_joint, message))
+}
+
+/// Hashes `dom2(flag, context)` of RFC 8032 into `h`
+fn update_dom2<CtxDigest>(h: &mut CtxDigest, flag: u8, context: &[u8]) -> Result<(), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let context_length = u8::try_from(context.len()).map_err(|_| SignError::ContextTooLong)?;
+    h.update(DOM2_PREFIX);
+    h.update([flag, context_length]);
+    h.update(context);
+    Ok(())
+}
+
+/// The second round of the distributed signing protocol before the secret share is applied
+///
+/// The joint nonce commitment `R` and the challenge `c` can be exported from it for approval. It is
+/// bound to the hash of the message that it was prepared for, so a different message cannot be
+/// substituted when the secret share is applied.
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PreparedSecondRound {
+    pub(crate) R: EdwardsPoint,
+    pub(crate) R_party: EdwardsPoint,
+    pub(crate) c: Scalar,
+    pub(crate) rho: Scalar,
+    message_digest: [u8; 64],
+}
+
+#[allow(non_snake_case)]
+impl PreparedSecondRound {
+    pub(crate) fn new<CtxDigest>(
+        R: EdwardsPoint,
+        R_party: EdwardsPoint,
+        c: Scalar,
+        rho: Scalar,
+        message: &[u8],
+    ) -> Self
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        let mut message_digest = [0u8; 64];
+        message_digest.copy_from_slice(&CtxDigest::digest(message));
+
+        Self {
+            R,
+            R_party,
+            c,
+            rho,
+            message_digest,
+        }
+    }
+
+    /// The joint nonce commitment `R` of the signature
+    pub fn nonce_commitment(&self) -> CompressedEdwardsY {
+        self.R.compress()
+    }
+
+    /// This party's own share `D + rho * E` of the joint nonce commitment
+    pub fn party_commitment(&self) -> CompressedEdwardsY {
+        self.R_party.compress()
+    }
+
+    /// The challenge `c` of the signature
+    pub fn challenge(&self) -> Scalar {
+        self.c
+    }
+
+    pub(crate) fn is_bound_to<CtxDigest>(&self, message: &[u8]) -> bool
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        self.message_digest[..] == CtxDigest::digest(message)[..]
+    }
+}
+
+/// The flag of the round 1 encoding that marks a session nonce as present
+const SESSION_NONCE_FLAG: u8 = 1;
+
+/// The flag of the round 1 encoding that marks a timestamp as present
+const TIMESTAMP_FLAG: u8 = 2;
+
+/// Encodes the fields of a round 1 message as `D || E || flags || session_nonce || timestamp`
+///
+/// `flags` marks which of the optional session nonce and timestamp follow. The timestamp is encoded
+/// in 8 little-endian bytes.
+#[allow(non_snake_case)]
+pub(crate) fn round1_to_bytes(
+    D: &CompressedEdwardsY,
+    E: &CompressedEdwardsY,
+    session_nonce: Option<[u8; 32]>,
+    timestamp: Option<u64>,
+) -> Vec<u8> {
+    let mut flags = 0;
+    let mut bytes = Vec::with_capacity(65 + 32 + 8);
+    bytes.extend_from_slice(D.as_bytes());
+    bytes.extend_from_slice(E.as_bytes());
+    bytes.push(0);
+    if let Some(session_nonce) = session_nonce {
+        flags |= SESSION_NONCE_FLAG;
+        bytes.extend_from_slice(&session_nonce);
+    }
+    if let Some(timestamp) = timestamp {
+        flags |= TIMESTAMP_FLAG;
+        bytes.extend_from_slice(&timestamp.to_le_bytes());
+    }
+    bytes[64] = flags;
+    bytes
+}
+
+/// Decodes the fields of a round 1 message encoded by `round1_to_bytes`
+#[allow(clippy::type_complexity)]
+pub(crate) fn round1_from_bytes(
+    bytes: &[u8],
+) -> Result<
+    (
+        CompressedEdwardsY,
+        CompressedEdwardsY,
+        Option<[u8; 32]>,
+        Option<u64>,
+    ),
+    SignError,
+> {
+    if bytes.len() < 65 {
+        return Err(SignError::MalformedMessage);
+    }
+    let flags = bytes[64];
+    if flags & !(SESSION_NONCE_FLAG | TIMESTAMP_FLAG) != 0 {
+        return Err(SignError::MalformedMessage);
+    }
+
+    let mut expected_length = 65;
+    if flags & SESSION_NONCE_FLAG != 0 {
+        expected_length += 32;
+    }
+    if flags & TIMESTAMP_FLAG != 0 {
+        expected_length += 8;
+    }
+    if bytes.len() != expected_length {
+        return Err(SignError::MalformedMessage);
+    }
+
+    let mut offset = 65;
+    let session_nonce = (flags & SESSION_NONCE_FLAG != 0).then(|| {
+        offset += 32;
+        array_at(bytes, offset - 32)
+    });
+    let timestamp = (flags & TIMESTAMP_FLAG != 0).then(|| {
+        let mut timestamp = [0u8; 8];
+        timestamp.copy_from_slice(&bytes[offset..offset + 8]);
+        u64::from_le_bytes(timestamp)
+    });
+
+    Ok((
+        point_at(bytes, 0),
+        point_at(bytes, 32),
+        session_nonce,
+        timestamp,
+    ))
+}
+
+/// Decodes the fields of a round 1 message from the base64 form written by its `Display` impl
+///
+/// The optional fields are told apart by the length of the string, which differs for each
+/// combination of them.
+#[allow(clippy::type_complexity)]
+pub(crate) fn round1_from_str(
+    s: &str,
+) -> Result<
+    (
+        CompressedEdwardsY,
+        CompressedEdwardsY,
+        Option<[u8; 32]>,
+        Option<u64>,
+    ),
+    SignError,
+> {
+    let (flags, lengths): (u8, &[usize]) = match s.len() {
+        88 => (0, &[32, 32]),
+        132 => (SESSION_NONCE_FLAG, &[32, 32, 32]),
+        100 => (TIMESTAMP_FLAG, &[32, 32, 8]),
+        144 => (SESSION_NONCE_FLAG | TIMESTAMP_FLAG, &[32, 32, 32, 8]),
+        _ => return Err(SignError::MalformedMessage),
+    };
+    let mut bytes = base64_segments(s, lengths).ok_or(SignError::MalformedMessage)?;
+    bytes.insert(64, flags);
+
+    round1_from_bytes(&bytes)
+}
+
+/// Computes a party's share `R_party = D + rho * E` of the joint nonce commitment
+///
+/// The commitments are public, so this uses a variable-time multiscalar multiplication.
+#[allow(non_snake_case)]
+pub(crate) fn commitment_share(D: &EdwardsPoint, E: &EdwardsPoint, rho: &Scalar) -> EdwardsPoint {
+    EdwardsPoint::vartime_multiscalar_mul([Scalar::ONE, *rho], [D, E])
+}
+
+/// Computes the hash that identifies the round 1 message of `party` with byte encoding
+/// `message_bytes`
+pub(crate) fn round1_commitment_hash<CtxDigest>(party: Party, message_bytes: &[u8]) -> [u8; 32]
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let mut h = CtxDigest::new();
+    h.update(b"frost sign round 1 commitment");
+    h.update(party.to_string());
+    h.update(message_bytes);
+
+    let mut commitment = [0u8; 32];
+    commitment.copy_from_slice(&h.finalize()[..32]);
+    commitment
+}
+
+/// Computes a binding factor `rho`, which ties a party's nonce commitments `D` and `E` to the
+/// message and to the rest of its round 1 message
+///
+/// The client and the server use the labels `b"client"` and `b"server"`, and pass the session
+/// nonce and the timestamp of their round 1 message if it has them. The session nonce is hashed in,
+/// so the joint `R` and with it the challenge depend on the session. The challenge itself stays the
+/// RFC 8032 one so that signatures remain standard.
+#[allow(non_snake_case)]
+pub fn binding_factor<CtxDigest>(
+    label: &[u8],
+    message: &[u8],
+    D: &CompressedEdwardsY,
+    E: &CompressedEdwardsY,
+    session_nonce: Option<[u8; 32]>,
+    timestamp: Option<u64>,
+) -> Scalar
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let mut h = CtxDigest::new();
+    h.update(label);
+    h.update(message);
+    h.update(D.as_bytes());
+    h.update(E.as_bytes());
+    if let Some(session_nonce) = session_nonce {
+        h.update(session_nonce);
+    }
+    if let Some(timestamp) = timestamp {
+        h.update(timestamp.to_le_bytes());
+    }
+    Scalar::from_hash(h)
+}
+
+/// Computes the client's binding factor `rho_client` from its round 1 message
+pub(crate) fn client_binding_factor<CtxDigest>(
+    message: &[u8],
+    client_message: &SignClientRound1,
+) -> Scalar
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    binding_factor::<CtxDigest>(
+        b"client",
+        message,
+        &client_message.D_client,
+        &client_message.E_client,
+        client_message.session_nonce,
+        client_message.timestamp,
+    )
+}
+
+/// Computes the server's binding factor `rho_server` from its round 1 message
+pub(crate) fn server_binding_factor<CtxDigest>(
+    message: &[u8],
+    server_message: &SignServerRound1,
+) -> Scalar
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    binding_factor::<CtxDigest>(
+        b"server",
+        message,
+        &server_message.D_server,
+        &server_message.E_server,
+        server_message.session_nonce,
+        server_message.timestamp,
+    )
+}
+
+/// Derives a hedged nonce from fresh randomness, the party's secret share, and the message, as in
+/// the nonce generation of RFC 9591
+///
+/// The randomness keeps the nonce fresh across signing sessions over the same message, while the
+/// secret share keeps it unpredictable when the randomness is weak.
+pub(crate) fn hedged_nonce<CtxDigest>(
+    rng: &mut (impl RngCore + CryptoRng),
+    label: &[u8],
+    secret: &Scalar,
+    message: &[u8],
+) -> Scalar
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let mut random_bytes = [0u8; 32];
+    rng.fill_bytes(&mut random_bytes);
+
+    let mut h = CtxDigest::new();
+    h.update(b"frost hedged nonce");
+    h.update(label);
+    h.update(random_bytes);
+    h.update(secret.as_bytes());
+    h.update(message);
+    Scalar::from_hash(h)
+}
+
+/// Derives a session ID from the round 1 messages of both parties
+///
+/// The ID is a hash of the nonce commitments of both parties, so the client and the server compute
+/// the same ID from the same pair of messages regardless of which one started the protocol.
+pub fn derive_session_id<CtxDigest>(
+    client_message: &SignClientRound1,
+    server_message: &SignServerRound1,
+) -> [u8; 32]
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let mut h = CtxDigest::new();
+    h.update(b"frost session id");
+    h.update(client_message.D_client.as_bytes());
+    h.update(client_message.E_client.as_bytes());
+    h.update(server_message.D_server.as_bytes());
+    h.update(server_message.E_server.as_bytes());
+
+    let mut session_id = [0u8; 32];
+    session_id.copy_from_slice(&h.finalize()[..32]);
+    session_id
+}
+
+/// Checks the timestamp of a peer's round 1 message against the current time `now`
+///
+/// Timestamps are in seconds since the Unix epoch and are set by the sender on its round 1 message
+/// before sending it. A timestamp enters the sender's binding factor, so altering it in transit
+/// makes the partial signatures fail to verify. A message without a timestamp, or with one that is
+/// more than `max_skew` seconds away from `now` in either direction, is rejected with
+/// `SignError::TimestampOutOfRange`.
+pub fn check_timestamp(timestamp: Option<u64>, now: u64, max_skew: u64) -> Result<(), SignError> {
+    match timestamp {
+        Some(timestamp) if timestamp.abs_diff(now) <= max_skew => Ok(()),
+        _ => Err(SignError::TimestampOutOfRange),
+    }
+}
+
+/// The public keys that `combine_sigs` needs, decompressed once so that repeated combinations
+/// under the same key skip the decompression
+///
+/// `P_peer` is the public key share of the party whose partial signature gets verified, which is
+/// `P_server` for the client and `P_client` for the server.
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PreparedCombiner {
+    pub(crate) P_joint: CompressedEdwardsY,
+    pub(crate) P_peer: EdwardsPoint,
+}
+
+#[allow(non_snake_case)]
+impl PreparedCombiner {
+    pub fn new(
+        P_joint: &CompressedEdwardsY,
+        P_peer: &CompressedEdwardsY,
+    ) -> Result<Self, SignError> {
+        let P_joint_point = P_joint.decompress().ok_or(SignError::Decompression)?;
+        let P_peer = P_peer.decompress().ok_or(SignError::Decompression)?;
+        check_public_key(&P_joint_point)?;
+        check_public_key(&P_peer)?;
+
+        Ok(Self {
+            P_joint: *P_joint,
+            P_peer,
+        })
+    }
+}
+
+/// Decompresses a public key and checks that it is a non-identity point of the prime-order
+/// subgroup
+///
+/// A public key with a small-order component lets its holder shift the partial signature check by
+/// a torsion point, so such keys are rejected before any signature is checked against them.
+#[allow(non_snake_case)]
+pub(crate) fn decompress_public_key(P: &CompressedEdwardsY) -> Result<EdwardsPoint, SignError> {
+    let P = P.decompress().ok_or(SignError::Decompression)?;
+    check_public_key(&P)?;
+    Ok(P)
+}
+
+#[allow(non_snake_case)]
+fn check_public_key(P: &EdwardsPoint) -> Result<(), SignError> {
+    if P.is_identity() || !P.is_torsion_free() {
+        return Err(SignError::WeakPublicKey);
+    }
+    Ok(())
+}
+
+/// Verifies a party's partial signature `z_party` given the joint nonce commitment `R` and the
+/// party's own share `R_party` of it
+///
+/// This is the check that `combine_sigs` performs on the peer's partial signature. It lets a
+/// coordinator that has already computed `R` and `R_party` skip rebuilding them from the round 1
+/// messages. A failure is reported as `SignError::PartialSignatureVerification` for `party`, and
+/// `P_party` is rejected with `SignError::WeakPublicKey` if it is the identity or has a small-order
+/// component.
+#[allow(non_snake_case)]
+pub fn verify_partial_with_R<CtxDigest>(
+    party: Party,
+    R: &CompressedEdwardsY,
+    R_party: &CompressedEdwardsY,
+    P_party: &CompressedEdwardsY,
+    message: &[u8],
+    P_joint: &CompressedEdwardsY,
+    z_party: &Scalar,
+) -> Result<(), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let P_party = decompress_public_key(P_party)?;
+    verify_partial_decompressed::<CtxDigest>(
+        party,
+        SigningVariant::Ed25519,
+        R,
+        R_party,
+        &P_party,
+        message,
+        P_joint,
+        z_party,
+    )
+}
+
+/// `verify_partial_with_R` with the party's public key share already decompressed
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub(crate) fn verify_partial_decompressed<CtxDigest>(
+    party: Party,
+    variant: SigningVariant,
+    R: &CompressedEdwardsY,
+    R_party: &CompressedEdwardsY,
+    P_party: &EdwardsPoint,
+    message: &[u8],
+    P_joint: &CompressedEdwardsY,
+    z_party: &Scalar,
+) -> Result<(), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let c = variant_challenge::<CtxDigest>(variant, R, P_joint, message)?;
+
+    // `z_party * G - c * P_party` must be equal to `R_party`. The values are public, but the
+    // comparison is constant-time in case they ever depend on a secret
+    let expected_R_party =
+        EdwardsPoint::vartime_double_scalar_mul_basepoint(&(-c), P_party, z_party);
+    if !bool::from(expected_R_party.compress().ct_eq(R_party)) {
+        return Err(SignError::PartialSignatureVerification { party });
+    }
+
+    Ok(())
+}
+
+/// Runs both parties of the distributed signing protocol in-process and returns the combined
+/// signature over `message`
+///
+/// This is a convenience for tests and for deployments where a single process holds both key
+/// shares. It runs the same round functions as two separate parties would, and verifies the
+/// combined signature under the joint public key of `client_share`.
+#[allow(non_snake_case)]
+pub fn two_party_sign<CtxDigest>(
+    client_share: &KeyShare,
+    server_share: &KeyShare,
+    message: &[u8],
+) -> Result<(CompressedEdwardsY, Scalar), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let (d_client, e_client, client_message_1) = ClientSign::first_round();
+    let (d_server, e_server, server_message_1) = ServerSign::first_round();
+
+    let (_, client_message_2) = ClientSign::second_round_with_share::<CtxDigest>(
+        client_share,
+        message,
+        &d_client,
+        &e_client,
+        &client_message_1,
+        &server_message_1,
+    )?;
+    let (_, server_message_2) = ServerSign::second_round_with_share::<CtxDigest>(
+        server_share,
+        message,
+        &d_server,
+        &e_server,
+        &client_message_1,
+        &server_message_1,
+    )?;
+
+    ClientSign::combine_sigs_verified::<CtxDigest>(
+        &client_share.joint_public().compress(),
+        &client_share.peer_public().compress(),
+        message,
+        &client_message_1,
+        &client_message_2,
+        &server_message_1,
+        &server_message_2,
+    )
+}
+
+/// Creates a single-signer Schnorr signature over `message` under the key pair `(x, X)`
+///
+/// The signature is checked with `verify_signature`, passing `X` as the public key.
+#[allow(non_snake_case)]
+pub fn schnorr_sign<CtxDigest>(
+    x: &Scalar,
+    X: &CompressedEdwardsY,
+    message: &[u8],
+) -> (CompressedEdwardsY, Scalar)
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let k = Scalar::random(&mut OsRng);
+    let R = EdwardsPoint::mul_base(&k).compress();
+
+    let c = signing_challenge::<CtxDigest>(&R, message, X);
+
+    (R, k + c * x)
+}
+
+/// The size of the chunks in which a streamed message is fed into the challenge hash
+const STREAM_CHUNK_SIZE: usize = 8192;
+
+/// Verifies a signature `(R, z)` over `message` under the joint public key
+#[allow(non_snake_case)]
+pub fn verify_signature<CtxDigest>(
+    P_joint: &CompressedEdwardsY,
+    message: &[u8],
+    R: &CompressedEdwardsY,
+    z: &Scalar,
+) -> Result<(), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    verify_signature_with_variant::<CtxDigest>(SigningVariant::Ed25519, P_joint, message, R, z)
+}
+
+/// `verify_signature` with SHA-512, as in Ed25519
+#[allow(non_snake_case)]
+pub fn verify_signature_sha512(
+    P_joint: &CompressedEdwardsY,
+    message: &[u8],
+    R: &CompressedEdwardsY,
+    z: &Scalar,
+) -> Result<(), SignError> {
+    verify_signature::<Sha512>(P_joint, message, R, z)
+}
+
+/// Verifies a signature `(R, z)` made under `variant` over `message` under the joint public key
+///
+/// For `SigningVariant::Ed25519ph`, `message` is the prehash of the actual message.
+#[allow(non_snake_case)]
+pub fn verify_signature_with_variant<CtxDigest>(
+    variant: SigningVariant,
+    P_joint: &CompressedEdwardsY,
+    message: &[u8],
+    R: &CompressedEdwardsY,
+    z: &Scalar,
+) -> Result<(), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let c = variant_challenge::<CtxDigest>(variant, R, P_joint, message)?;
+
+    verify_with_challenge(P_joint, R, z, &c)
+}
+
+/// Encodes a signature `(R, z)` as a standard 64-byte Ed25519 signature
+///
+/// The encoding is `R || s` as in RFC 8032: the 32-byte compressed `R` followed by the 32-byte
+/// little-endian `s = z`. Any Ed25519 verifier accepts it under `P_joint`.
+///
+/// A signature from `combine_sigs` also passes the strict checks that some verifiers add, such as
+/// `verify_strict` of ed25519-dalek, as well as the more permissive ZIP-215 rules. `s` is always
+/// canonical, the second round refuses an `R` that is the identity or has a small-order component,
+/// and `combine_sigs` refuses a `P_joint` with a small-order component.
+#[allow(non_snake_case)]
+pub fn to_signature_bytes(R: &CompressedEdwardsY, z: &Scalar) -> [u8; 64] {
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(R.as_bytes());
+    signature[32..].copy_from_slice(z.as_bytes());
+    signature
+}
+
+/// Decodes a 64-byte Ed25519 signature `R || s` encoded by `to_signature_bytes` into `(R, z)`
+///
+/// A signature whose `s` is not canonically encoded fails with `SignError::NonCanonicalScalar`,
+/// so a malleated copy of a signature is never accepted. `R` is not decompressed here.
+#[allow(non_snake_case)]
+pub fn from_signature_bytes(
+    signature: &[u8; 64],
+) -> Result<(CompressedEdwardsY, Scalar), SignError> {
+    let R = point_at(signature, 0);
+    let z = scalar_at(signature, 32).ok_or(SignError::NonCanonicalScalar)?;
+    Ok((R, z))
+}
+
+/// Verifies a standard 64-byte Ed25519 signature `R || s` over `message` under the joint public
+/// key
+///
+/// This accepts signatures made by any RFC 8032 implementation under the key `P_joint`, which
+/// hashes with SHA-512. A signature whose `s` is not canonically encoded is rejected.
+#[allow(non_snake_case)]
+pub fn verify_external_signature(
+    P_joint: &CompressedEdwardsY,
+    message: &[u8],
+    ext_sig: &[u8; 64],
+) -> Result<(), SignError> {
+    let (R, s) = from_signature_bytes(ext_sig).map_err(|_| SignError::SignatureVerification)?;
+
+    verify_signature::<Sha512>(P_joint, message, &R, &s)
+}
+
+/// Verifies a signature `(R, z)` over a message that is read from `reader`
+///
+/// The message is fed into the challenge hash in chunks, so it never has to be held in memory as
+/// a whole. The result is the same as `verify_signature` on the same content.
+#[allow(non_snake_case)]
+pub fn verify_streaming<CtxDigest>(
+    P_joint: &CompressedEdwardsY,
+    reader: impl Read,
+    R: &CompressedEdwardsY,
+    z: &Scalar,
+) -> Result<(), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    verify_streaming_with_limit::<CtxDigest>(P_joint, reader, R, z, u64::MAX)
+}
+
+/// `verify_streaming` that stops reading once the message exceeds `max_bytes`
+///
+/// A message longer than `max_bytes` fails with `SignError::MessageTooLarge` as soon as the chunk
+/// that crosses the limit is read, so an unbounded `reader` cannot keep the hash running forever.
+#[allow(non_snake_case)]
+pub fn verify_streaming_with_limit<CtxDigest>(
+    P_joint: &CompressedEdwardsY,
+    reader: impl Read,
+    R: &CompressedEdwardsY,
+    z: &Scalar,
+    max_bytes: u64,
+) -> Result<(), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let mut h = CtxDigest::new();
+    h.update(R.as_bytes());
+    h.update(P_joint.as_bytes());
+
+    update_from_reader(&mut h, reader, max_bytes)?;
+    let c = Scalar::from_hash(h);
+
+    verify_with_challenge(P_joint, R, z, &c)
+}
+
+/// Feeds everything that `reader` yields into `h` in chunks of `STREAM_CHUNK_SIZE` bytes
+fn update_from_reader(
+    h: &mut impl Digest,
+    mut reader: impl Read,
+    max_bytes: u64,
+) -> Result<(), SignError> {
+    let mut chunk = [0u8; STREAM_CHUNK_SIZE];
+    let mut total: u64 = 0;
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => return Ok(()),
+            Ok(n) => {
+                total = total.saturating_add(n as u64);
+                if total > max_bytes {
+                    return Err(SignError::MessageTooLarge);
+                }
+                h.update(&chunk[..n]);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(SignError::MessageRead(e.kind())),
+        }
+    }
+}
+
+/// The values of a failed signature verification, for diagnosing why it failed
+///
+/// A valid signature has `reconstructed_R == R`. If the two differ, the message, the public key, or
+/// the encoding of the signature is not what the signer used.
+#[allow(non_snake_case)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VerifyDiagnostics {
+    /// The error that `verify_signature` would have returned
+    pub error: SignError,
+    /// The challenge recomputed from `R`, the message, and the public key
+    pub challenge: Scalar,
+    /// The nonce commitment carried in the signature
+    pub R: CompressedEdwardsY,
+    /// The nonce commitment `z * G - c * P_joint` reconstructed from the signature, or `None` if
+    /// the public key failed to decompress
+    pub reconstructed_R: Option<CompressedEdwardsY>,
+}
+
+/// Verifies a signature `(R, z)` like `verify_signature`, but returns the recomputed challenge and
+/// both sides of the verification equation on failure
+#[allow(non_snake_case)]
+pub fn verify_verbose<CtxDigest>(
+    P_joint: &CompressedEdwardsY,
+    message: &[u8],
+    R: &CompressedEdwardsY,
+    z: &Scalar,
+) -> Result<(), VerifyDiagnostics>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let c = signing_challenge::<CtxDigest>(R, message, P_joint);
+
+    let reconstructed_R = reconstruct_R(P_joint, z, &c);
+    let (error, reconstructed_R) = match reconstructed_R {
+        Ok(reconstructed_R) if reconstructed_R == *R => return Ok(()),
+        Ok(reconstructed_R) => (SignError::SignatureVerification, Some(reconstructed_R)),
+        Err(e) => (e, None),
+    };
+
+    Err(VerifyDiagnostics {
+        error,
+        challenge: c,
+        R: *R,
+        reconstructed_R,
+    })
+}
+
+/// Checks the verification equation `z * G == R + c * P_joint`
+#[allow(non_snake_case)]
+fn verify_with_challenge(
+    P_joint: &CompressedEdwardsY,
+    R: &CompressedEdwardsY,
+    z: &Scalar,
+    c: &Scalar,
+) -> Result<(), SignError> {
+    if !bool::from(reconstruct_R(P_joint, z, c)?.ct_eq(R)) {
+        return Err(SignError::SignatureVerification);
+    }
+
+    Ok(())
+}
+
+/// Computes `z * G - c * P_joint`, which is equal to `R` for a valid signature
+#[allow(non_snake_case)]
+fn reconstruct_R(
+    P_joint: &CompressedEdwardsY,
+    z: &Scalar,
+    c: &Scalar,
+) -> Result<CompressedEdwardsY, SignError> {
+    let P_joint = P_joint.decompress().ok_or(SignError::Decompression)?;
+    Ok(EdwardsPoint::vartime_double_scalar_mul_basepoint(&(-c), &P_joint, z).compress())
+}
+
+/// Splits the nonce commitment `R` of a signature into its y-coordinate and the parity of its
+/// x-coordinate
+///
+/// This is the Ed25519 point encoding taken apart: the returned coordinate is `y`, with the top bit
+/// cleared, and the parity is the sign of `x` that was stored in that top bit. It is not the BIP340
+/// x-only encoding, which keeps the x-coordinate and drops the parity of `y`. The point is
+/// recovered with `r_from_y_and_parity`.
+#[allow(non_snake_case)]
+pub fn r_y_and_parity(R: &CompressedEdwardsY) -> ([u8; 32], bool) {
+    let mut coordinate = R.to_bytes();
+    let parity = coordinate[31] >> 7 == 1;
+    coordinate[31] &= 0x7f;
+    (coordinate, parity)
+}
+
+/// Recovers the nonce commitment `R` from the output of `r_y_and_parity`
+#[allow(non_snake_case)]
+pub fn r_from_y_and_parity(
+    coordinate: &[u8; 32],
+    parity: bool,
+) -> Result<CompressedEdwardsY, SignError> {
+    let mut bytes = *coordinate;
+    bytes[31] = (bytes[31] & 0x7f) | ((parity as u8) << 7);
+
+    let R = CompressedEdwardsY(bytes);
+    R.decompress().ok_or(SignError::Decompression)?;
+    Ok(R)
+}
+
+/// Converts the joint public key to the u-coordinate of the equivalent X25519 public key
+///
+/// This is the birational map `u = (1 + y) / (1 - y)` from the Edwards curve to the Montgomery
+/// curve. The result identifies the joint key in X25519 encoding only. It is not a key agreement
+/// key: X25519 clamps its secret scalar, which the joint secret key `p_joint` is not, and no party
+/// holds `p_joint`, since it only exists split between the two key shares.
+#[allow(non_snake_case)]
+pub fn joint_key_to_montgomery(P_joint: &CompressedEdwardsY) -> Result<[u8; 32], SignError> {
+    let P_joint = P_joint.decompress().ok_or(SignError::Decompression)?;
+    Ok(P_joint.to_montgomery().to_bytes())
 }
@@ -0,0 +1,94 @@
+//! Receipts that let a coordinator prove which partial signature each party contributed
+
+use curve25519_dalek::{
+    digest::{generic_array::typenum::U64, Digest},
+    edwards::CompressedEdwardsY,
+    scalar::Scalar,
+};
+
+use crate::{
+    dkg::party_label,
+    sign::{schnorr_sign, verify_signature, SignError},
+    Party,
+};
+
+/// The domain separation label of the message that a receipt signs
+const RECEIPT_LABEL: &[u8] = b"frost signing receipt";
+
+/// A party's signed statement that it contributed the partial signature `z` with the nonce
+/// commitment `R_party` to the signing session `session_id`
+///
+/// The statement is signed with a single-signer Schnorr signature `(signature_R, signature_z)`
+/// under the party's public key share.
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SigningReceipt {
+    pub party: Party,
+    pub R_party: CompressedEdwardsY,
+    pub z: Scalar,
+    pub session_id: [u8; 32],
+    pub signature_R: CompressedEdwardsY,
+    pub signature_z: Scalar,
+}
+
+/// Issues a receipt for a partial signature, signed under the party's key share `(p, P)`
+#[allow(non_snake_case)]
+pub fn issue_receipt<CtxDigest>(
+    party: Party,
+    p: &Scalar,
+    P: &CompressedEdwardsY,
+    R_party: &CompressedEdwardsY,
+    z: &Scalar,
+    session_id: &[u8; 32],
+) -> SigningReceipt
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let statement = receipt_statement(party, R_party, z, session_id);
+    let (signature_R, signature_z) = schnorr_sign::<CtxDigest>(p, P, &statement);
+
+    SigningReceipt {
+        party,
+        R_party: *R_party,
+        z: *z,
+        session_id: *session_id,
+        signature_R,
+        signature_z,
+    }
+}
+
+/// Verifies a receipt against the public key share `P` of the party that issued it
+#[allow(non_snake_case)]
+pub fn verify_receipt<CtxDigest>(
+    receipt: &SigningReceipt,
+    P: &CompressedEdwardsY,
+) -> Result<(), SignError>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+{
+    let statement = receipt_statement(
+        receipt.party,
+        &receipt.R_party,
+        &receipt.z,
+        &receipt.session_id,
+    );
+    verify_signature::<CtxDigest>(P, &statement, &receipt.signature_R, &receipt.signature_z)
+}
+
+#[allow(non_snake_case)]
+fn receipt_statement(
+    party: Party,
+    R_party: &CompressedEdwardsY,
+    z: &Scalar,
+    session_id: &[u8; 32],
+) -> Vec<u8> {
+    let party_label = party_label(party);
+
+    let mut statement = Vec::with_capacity(RECEIPT_LABEL.len() + party_label.len() + 96);
+    statement.extend_from_slice(RECEIPT_LABEL);
+    statement.extend_from_slice(party_label);
+    statement.extend_from_slice(R_party.as_bytes());
+    statement.extend_from_slice(z.as_bytes());
+    statement.extend_from_slice(session_id);
+    statement
+}
@@ -1,28 +1,125 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
 
 use base64::{prelude::BASE64_STANDARD, Engine};
 use curve25519_dalek::{
     digest::{generic_array::typenum::U64, Digest},
     edwards::{CompressedEdwardsY, EdwardsPoint},
     scalar::Scalar,
+    traits::IsIdentity,
 };
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use sha2::Sha512;
+use zeroize::Zeroize;
 
-use crate::sign::{client::*, SignError};
+use crate::{
+    key_share::KeyShare,
+    sign::{
+        check_timestamp, client::*, client_binding_factor, commitment_share,
+        coordinator::PartialSignatureMessage, derive_session_id, hedged_nonce,
+        round1_commitment_hash, round1_from_bytes, round1_from_str, round1_to_bytes,
+        server_binding_factor, variant_challenge, verify_partial_decompressed, verify_signature,
+        PreparedCombiner, PreparedSecondRound, SignError, SigningVariant,
+    },
+    wire::{base64_segments, scalar_at},
+    Party,
+};
 
 /// The message that the server sends over to the client at round 1 of the distributed signing
 /// protocol
 #[allow(non_snake_case)]
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct SignServerRound1 {
     pub D_server: CompressedEdwardsY,
     pub E_server: CompressedEdwardsY,
+    pub session_nonce: Option<[u8; 32]>,
+    pub timestamp: Option<u64>,
 }
 
 impl Display for SignServerRound1 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{}", BASE64_STANDARD.encode(self.D_server.as_bytes()))?;
-        write!(f, "{}", BASE64_STANDARD.encode(self.E_server.as_bytes()))
+        write!(f, "{}", BASE64_STANDARD.encode(self.E_server.as_bytes()))?;
+        if let Some(session_nonce) = self.session_nonce {
+            write!(f, "{}", BASE64_STANDARD.encode(session_nonce))?;
+        }
+        if let Some(timestamp) = self.timestamp {
+            write!(f, "{}", BASE64_STANDARD.encode(timestamp.to_le_bytes()))?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(non_snake_case)]
+impl FromStr for SignServerRound1 {
+    type Err = SignError;
+
+    /// Decodes a message from the base64 form written by its `Display` impl
+    fn from_str(s: &str) -> Result<Self, SignError> {
+        let (D_server, E_server, session_nonce, timestamp) = round1_from_str(s)?;
+
+        Ok(Self {
+            D_server,
+            E_server,
+            session_nonce,
+            timestamp,
+        })
+    }
+}
+
+/// Orders the messages by their byte encodings, so that they can be used as keys of a `BTreeMap`
+impl Ord for SignServerRound1 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_bytes().cmp(&other.to_bytes())
+    }
+}
+
+impl PartialOrd for SignServerRound1 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[allow(non_snake_case)]
+impl SignServerRound1 {
+    /// Encodes the message as `D_server || E_server || flags || session_nonce || timestamp`
+    ///
+    /// `flags` is a byte whose lowest bit marks the session nonce as present and whose second
+    /// lowest bit marks the timestamp as present. Absent fields are left out, and the timestamp is
+    /// encoded in 8 little-endian bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        round1_to_bytes(
+            &self.D_server,
+            &self.E_server,
+            self.session_nonce,
+            self.timestamp,
+        )
+    }
+
+    /// The hash that identifies the message, which a party records together with its nonces to
+    /// tell its concurrent signing sessions apart
+    pub fn commitment_hash<CtxDigest>(&self) -> [u8; 32]
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        round1_commitment_hash::<CtxDigest>(Party::Server, &self.to_bytes())
+    }
+
+    /// Decodes a message encoded by `to_bytes`
+    ///
+    /// The points are not decompressed here, so an invalid point is only caught by the round that
+    /// uses it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SignError> {
+        let (D_server, E_server, session_nonce, timestamp) = round1_from_bytes(bytes)?;
+        Ok(Self {
+            D_server,
+            E_server,
+            session_nonce,
+            timestamp,
+        })
     }
 }
 
@@ -40,6 +137,37 @@ impl Display for SignServerRound2 {
     }
 }
 
+impl FromStr for SignServerRound2 {
+    type Err = SignError;
+
+    /// Decodes a message from the base64 form written by its `Display` impl
+    fn from_str(s: &str) -> Result<Self, SignError> {
+        let bytes = base64_segments(s, &[32]).ok_or(SignError::MalformedMessage)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl SignServerRound2 {
+    /// The length of the byte encoding of the message
+    pub const LENGTH: usize = 32;
+
+    /// Encodes the message as `z_server`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.z_server.as_bytes().to_vec()
+    }
+
+    /// Decodes a message encoded by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SignError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(SignError::MalformedMessage);
+        }
+
+        Ok(Self {
+            z_server: scalar_at(bytes, 0).ok_or(SignError::NonCanonicalScalar)?,
+        })
+    }
+}
+
 pub struct ServerSign;
 #[allow(non_snake_case)]
 impl ServerSign {
@@ -47,10 +175,81 @@ impl ServerSign {
     ///
     /// It does not matter whether the client or the server starts the protocol first.
     pub fn first_round() -> (Scalar, Scalar, SignServerRound1) {
+        Self::first_round_with_rng(&mut OsRng)
+    }
+
+    /// The server logic for the first round of the distributed signing protocol, drawing its
+    /// randomness from `rng`
+    pub fn first_round_with_rng(
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (Scalar, Scalar, SignServerRound1) {
+        Self::first_round_with_session_inner(rng, None, None)
+    }
+
+    /// The server logic for the first round of the distributed signing protocol, binding the
+    /// commitments to a signing session
+    ///
+    /// Both parties must use the same `session_nonce`, typically chosen by the party that starts
+    /// the session and echoed by its peer. The second round rejects commitments from a different
+    /// session with `SignError::StaleCommitment`, so that a commitment captured in one session
+    /// cannot be replayed into another.
+    pub fn first_round_with_session(
+        rng: &mut (impl RngCore + CryptoRng),
+        session_nonce: [u8; 32],
+    ) -> (Scalar, Scalar, SignServerRound1) {
+        Self::first_round_with_session_inner(rng, Some(session_nonce), None)
+    }
+
+    /// The server logic for the first round of the distributed signing protocol with a timestamp
+    ///
+    /// `timestamp` is the current time in seconds since the Unix epoch. It enters the server's
+    /// binding factor, so it cannot be altered in transit, and the client rejects a stale
+    /// message in `ClientSign::second_round_with_timestamp`.
+    pub fn first_round_with_timestamp(
+        rng: &mut (impl RngCore + CryptoRng),
+        timestamp: u64,
+    ) -> (Scalar, Scalar, SignServerRound1) {
+        Self::first_round_with_session_inner(rng, None, Some(timestamp))
+    }
+
+    fn first_round_with_session_inner(
+        rng: &mut (impl RngCore + CryptoRng),
+        session_nonce: Option<[u8; 32]>,
+        timestamp: Option<u64>,
+    ) -> (Scalar, Scalar, SignServerRound1) {
         // 1. Generates two random scalar elements
-        let d_server = Scalar::random(&mut OsRng);
-        let e_server = Scalar::random(&mut OsRng);
+        let d_server = Scalar::random(rng);
+        let e_server = Scalar::random(rng);
+
+        Self::first_round_with_nonces(d_server, e_server, session_nonce, timestamp)
+    }
+
+    /// The server logic for the first round of the distributed signing protocol with hedged
+    /// nonces
+    ///
+    /// The nonces are derived from fresh randomness drawn from `rng`, the server's secret share,
+    /// and the message to be signed, so that they stay unpredictable on a platform with weak
+    /// entropy. The nonces must still be used for a single session only.
+    pub fn first_round_hedged<CtxDigest>(
+        rng: &mut (impl RngCore + CryptoRng),
+        p_server: &Scalar,
+        message: &[u8],
+    ) -> (Scalar, Scalar, SignServerRound1)
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        let d_server = hedged_nonce::<CtxDigest>(rng, b"server d", p_server, message);
+        let e_server = hedged_nonce::<CtxDigest>(rng, b"server e", p_server, message);
+
+        Self::first_round_with_nonces(d_server, e_server, None, None)
+    }
 
+    fn first_round_with_nonces(
+        d_server: Scalar,
+        e_server: Scalar,
+        session_nonce: Option<[u8; 32]>,
+        timestamp: Option<u64>,
+    ) -> (Scalar, Scalar, SignServerRound1) {
         // 2. Commits to the two scalar elements above as elliptic curve points
         let D_server = EdwardsPoint::mul_base(&d_server);
         let E_server = EdwardsPoint::mul_base(&e_server);
@@ -59,12 +258,18 @@ impl ServerSign {
         let server_message = SignServerRound1 {
             D_server: D_server.compress(),
             E_server: E_server.compress(),
+            session_nonce,
+            timestamp,
         };
 
         (d_server, e_server, server_message)
     }
 
     /// The server logic for the second round of the distributed signing protocol
+    ///
+    /// The nonces `d_server` and `e_server` must never be used again after this call. Signing a
+    /// second message with them reveals the server's secret share. `ServerSignRound1State` owns the
+    /// nonces and enforces this at compile time.
     pub fn second_round<CtxDigest>(
         p_server: &Scalar,
         P_joint: &CompressedEdwardsY,
@@ -77,19 +282,255 @@ impl ServerSign {
     where
         CtxDigest: Digest<OutputSize = U64>,
     {
-        let mut h_client = CtxDigest::new();
-        h_client.update(b"client");
-        h_client.update(message);
-        h_client.update(client_message.D_client.as_bytes());
-        h_client.update(client_message.E_client.as_bytes());
-        let rho_client = Scalar::from_hash(h_client);
+        Self::second_round_with_variant::<CtxDigest>(
+            SigningVariant::Ed25519,
+            p_server,
+            P_joint,
+            message,
+            d_server,
+            e_server,
+            client_message,
+            server_message,
+        )
+    }
+
+    /// `second_round` for a server that runs several signing sessions at once
+    ///
+    /// `expected_commitment` is the `commitment_hash` of the server's round 1 message, recorded
+    /// together with `d_server` and `e_server` when the session started. A round 1 message of another
+    /// session fails with `SignError::CommitmentMismatch` instead of being signed with the nonces
+    /// of this one.
+    #[allow(clippy::too_many_arguments)]
+    pub fn second_round_with_commitment<CtxDigest>(
+        p_server: &Scalar,
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+        d_server: &Scalar,
+        e_server: &Scalar,
+        client_message: &SignClientRound1,
+        server_message: &SignServerRound1,
+        expected_commitment: &[u8; 32],
+    ) -> Result<(EdwardsPoint, SignServerRound2), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        if server_message.commitment_hash::<CtxDigest>() != *expected_commitment {
+            return Err(SignError::CommitmentMismatch);
+        }
+
+        Self::second_round::<CtxDigest>(
+            p_server,
+            P_joint,
+            message,
+            d_server,
+            e_server,
+            client_message,
+            server_message,
+        )
+    }
+
+    /// The server logic for the second round of the distributed signing protocol with the
+    /// server's key share
+    pub fn second_round_with_share<CtxDigest>(
+        key_share: &KeyShare,
+        message: &[u8],
+        d_server: &Scalar,
+        e_server: &Scalar,
+        client_message: &SignClientRound1,
+        server_message: &SignServerRound1,
+    ) -> Result<(EdwardsPoint, SignServerRound2), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        Self::second_round::<CtxDigest>(
+            key_share.private_share(),
+            &key_share.joint_public().compress(),
+            message,
+            d_server,
+            e_server,
+            client_message,
+            server_message,
+        )
+    }
+
+    /// `second_round` that also returns the server's partial signature for a coordinator
+    ///
+    /// The `PartialSignatureMessage` carries the server's share `R_server` of the joint nonce
+    /// commitment with its partial signature, so a coordinator can check the contribution with
+    /// `coordinator::verify_partial` as soon as it arrives, before the other party's is in.
+    pub fn second_round_with_partial<CtxDigest>(
+        p_server: &Scalar,
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+        d_server: &Scalar,
+        e_server: &Scalar,
+        client_message: &SignClientRound1,
+        server_message: &SignServerRound1,
+    ) -> Result<(EdwardsPoint, SignServerRound2, PartialSignatureMessage), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        let prepared = Self::prepare_second_round::<CtxDigest>(
+            P_joint,
+            message,
+            client_message,
+            server_message,
+        )?;
+        let server_message_2 = Self::finish_with_secret::<CtxDigest>(
+            &prepared, message, p_server, d_server, e_server,
+        )?;
+        let partial = PartialSignatureMessage {
+            role: Party::Server,
+            R_party: prepared.party_commitment(),
+            z: server_message_2.z_server,
+            session_id: derive_session_id::<CtxDigest>(client_message, server_message),
+        };
+
+        Ok((prepared.R, server_message_2, partial))
+    }
+
+    /// The server logic for the second round of the distributed signing protocol under an RFC
+    /// 8032 variant of Ed25519
+    ///
+    /// For `SigningVariant::Ed25519ph`, `message` is the prehash of the actual message.
+    #[allow(clippy::too_many_arguments)]
+    pub fn second_round_with_variant<CtxDigest>(
+        variant: SigningVariant,
+        p_server: &Scalar,
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+        d_server: &Scalar,
+        e_server: &Scalar,
+        client_message: &SignClientRound1,
+        server_message: &SignServerRound1,
+    ) -> Result<(EdwardsPoint, SignServerRound2), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        let prepared = Self::prepare_second_round_with_variant::<CtxDigest>(
+            variant,
+            P_joint,
+            message,
+            client_message,
+            server_message,
+        )?;
+        let server_message = Self::finish_with_secret::<CtxDigest>(
+            &prepared, message, p_server, d_server, e_server,
+        )?;
 
-        let mut h_server = CtxDigest::new();
-        h_server.update(b"server");
-        h_server.update(message);
-        h_server.update(server_message.D_server.as_bytes());
-        h_server.update(server_message.E_server.as_bytes());
-        let rho_server = Scalar::from_hash(h_server);
+        Ok((prepared.R, server_message))
+    }
+
+    /// The server logic for the second round of the distributed signing protocol, gated on a
+    /// spending policy
+    ///
+    /// `policy` is evaluated on the message before anything else. If it rejects the message, the
+    /// server withholds its partial signature and fails with `SignError::PolicyRejected`, so the
+    /// client cannot complete a signature over it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn second_round_with_policy<CtxDigest>(
+        p_server: &Scalar,
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+        d_server: &Scalar,
+        e_server: &Scalar,
+        client_message: &SignClientRound1,
+        server_message: &SignServerRound1,
+        policy: impl Fn(&[u8]) -> bool,
+    ) -> Result<(EdwardsPoint, SignServerRound2), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        if !policy(message) {
+            return Err(SignError::PolicyRejected);
+        }
+
+        Self::second_round::<CtxDigest>(
+            p_server,
+            P_joint,
+            message,
+            d_server,
+            e_server,
+            client_message,
+            server_message,
+        )
+    }
+
+    /// The server logic for the second round of the distributed signing protocol, rejecting a
+    /// stale client message
+    ///
+    /// The timestamp of `client_message` is checked with `check_timestamp` against `now` before
+    /// anything else. A client message without a timestamp, or with one more than `max_skew`
+    /// seconds away from `now`, fails with `SignError::TimestampOutOfRange` and is not signed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn second_round_with_timestamp<CtxDigest>(
+        p_server: &Scalar,
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+        d_server: &Scalar,
+        e_server: &Scalar,
+        client_message: &SignClientRound1,
+        server_message: &SignServerRound1,
+        now: u64,
+        max_skew: u64,
+    ) -> Result<(EdwardsPoint, SignServerRound2), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        check_timestamp(client_message.timestamp, now, max_skew)?;
+
+        Self::second_round::<CtxDigest>(
+            p_server,
+            P_joint,
+            message,
+            d_server,
+            e_server,
+            client_message,
+            server_message,
+        )
+    }
+
+    /// The public part of the server's second round of the distributed signing protocol
+    ///
+    /// This computes the joint nonce commitment `R` and the challenge `c` without using any secret,
+    /// so that the signing request can be approved before the secret share is loaded. The secret
+    /// share is then applied with `finish_with_secret`.
+    pub fn prepare_second_round<CtxDigest>(
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+        client_message: &SignClientRound1,
+        server_message: &SignServerRound1,
+    ) -> Result<PreparedSecondRound, SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        Self::prepare_second_round_with_variant::<CtxDigest>(
+            SigningVariant::Ed25519,
+            P_joint,
+            message,
+            client_message,
+            server_message,
+        )
+    }
+
+    /// `prepare_second_round` under an RFC 8032 variant of Ed25519
+    pub fn prepare_second_round_with_variant<CtxDigest>(
+        variant: SigningVariant,
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+        client_message: &SignClientRound1,
+        server_message: &SignServerRound1,
+    ) -> Result<PreparedSecondRound, SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        if client_message.session_nonce != server_message.session_nonce {
+            return Err(SignError::StaleCommitment);
+        }
+
+        let rho_client = client_binding_factor::<CtxDigest>(message, client_message);
+
+        let rho_server = server_binding_factor::<CtxDigest>(message, server_message);
 
         let D_client = client_message
             .D_client
@@ -107,18 +548,57 @@ impl ServerSign {
             .E_server
             .decompress()
             .ok_or(SignError::Decompression)?;
-        let R = D_client + E_client * rho_client + D_server + E_server * rho_server;
 
-        let mut h = CtxDigest::new();
-        h.update(R.compress().as_bytes());
-        h.update(message);
-        h.update(P_joint.as_bytes());
-        let c = Scalar::from_hash(h);
+        // An identity commitment drops the corresponding nonce out of `R`, so that it no longer
+        // hides the secret share of the party that signs with it
+        if [D_client, E_client, D_server, E_server]
+            .iter()
+            .any(|point| point.is_identity())
+        {
+            return Err(SignError::InvalidCommitment);
+        }
+
+        let R_client = commitment_share(&D_client, &E_client, &rho_client);
+        let R_server = commitment_share(&D_server, &E_server, &rho_server);
+        let R = R_client + R_server;
+
+        // An honest `R` lies in the prime-order subgroup, so a small-order component can only come
+        // from maliciously chosen commitments
+        if R.is_identity() || !R.is_torsion_free() {
+            return Err(SignError::WeakCommitment);
+        }
+
+        let c = variant_challenge::<CtxDigest>(variant, &R.compress(), P_joint, message)?;
+
+        Ok(PreparedSecondRound::new::<CtxDigest>(
+            R, R_server, c, rho_server, message,
+        ))
+    }
+
+    /// Applies the server's secret share and nonces to a prepared second round
+    ///
+    /// Fails with `SignError::MessageMismatch` if `message` is not the message that the second
+    /// round was prepared for.
+    pub fn finish_with_secret<CtxDigest>(
+        prepared: &PreparedSecondRound,
+        message: &[u8],
+        p_server: &Scalar,
+        d_server: &Scalar,
+        e_server: &Scalar,
+    ) -> Result<SignServerRound2, SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        #[cfg(feature = "fault-injection")]
+        crate::fault::take_sign_fault(crate::fault::SignStep::ServerSecondRound)?;
 
-        let z_server = d_server + e_server * rho_server - p_server * c;
+        if !prepared.is_bound_to::<CtxDigest>(message) {
+            return Err(SignError::MessageMismatch);
+        }
+
+        let z_server = d_server + e_server * prepared.rho + p_server * prepared.c;
 
-        let server_message = SignServerRound2 { z_server };
-        Ok((R, server_message))
+        Ok(SignServerRound2 { z_server })
     }
 
     /// The final step to combine the partial signatures to a full signature
@@ -134,12 +614,131 @@ impl ServerSign {
     where
         CtxDigest: Digest<OutputSize = U64>,
     {
-        let mut h_client = CtxDigest::new();
-        h_client.update(b"client");
-        h_client.update(message);
-        h_client.update(client_message_1.D_client.as_bytes());
-        h_client.update(client_message_1.E_client.as_bytes());
-        let rho_client = Scalar::from_hash(h_client);
+        let combiner = PreparedCombiner::new(P_joint, P_client)?;
+        Self::combine_sigs_prepared::<CtxDigest>(
+            &combiner,
+            message,
+            client_message_1,
+            client_message_2,
+            server_message_1,
+            server_message_2,
+        )
+    }
+
+    /// `combine_sigs` followed by a verification of the combined signature under `P_joint`
+    ///
+    /// `combine_sigs` only verifies the peer's partial signature. This also catches a bad partial
+    /// signature of our own, failing with `SignError::AggregateInvalid`.
+    pub fn combine_sigs_verified<CtxDigest>(
+        P_joint: &CompressedEdwardsY,
+        P_client: &CompressedEdwardsY,
+        message: &[u8],
+        client_message_1: &SignClientRound1,
+        client_message_2: &SignClientRound2,
+        server_message_1: &SignServerRound1,
+        server_message_2: &SignServerRound2,
+    ) -> Result<(CompressedEdwardsY, Scalar), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        let (R_joint, z_joint) = Self::combine_sigs::<CtxDigest>(
+            P_joint,
+            P_client,
+            message,
+            client_message_1,
+            client_message_2,
+            server_message_1,
+            server_message_2,
+        )?;
+
+        verify_signature::<CtxDigest>(P_joint, message, &R_joint, &z_joint).map_err(|error| {
+            match error {
+                SignError::SignatureVerification => SignError::AggregateInvalid,
+                error => error,
+            }
+        })?;
+
+        Ok((R_joint, z_joint))
+    }
+
+    /// `combine_sigs` with the public keys decompressed ahead of time
+    pub fn combine_sigs_prepared<CtxDigest>(
+        combiner: &PreparedCombiner,
+        message: &[u8],
+        client_message_1: &SignClientRound1,
+        client_message_2: &SignClientRound2,
+        server_message_1: &SignServerRound1,
+        server_message_2: &SignServerRound2,
+    ) -> Result<(CompressedEdwardsY, Scalar), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        Self::combine_prepared_with_variant::<CtxDigest>(
+            SigningVariant::Ed25519,
+            combiner,
+            message,
+            client_message_1,
+            client_message_2,
+            server_message_1,
+            server_message_2,
+        )
+    }
+
+    /// `combine_sigs` under an RFC 8032 variant of Ed25519
+    ///
+    /// For `SigningVariant::Ed25519ph`, `message` is the prehash of the actual message.
+    #[allow(clippy::too_many_arguments)]
+    pub fn combine_sigs_with_variant<CtxDigest>(
+        variant: SigningVariant,
+        P_joint: &CompressedEdwardsY,
+        P_client: &CompressedEdwardsY,
+        message: &[u8],
+        client_message_1: &SignClientRound1,
+        client_message_2: &SignClientRound2,
+        server_message_1: &SignServerRound1,
+        server_message_2: &SignServerRound2,
+    ) -> Result<(CompressedEdwardsY, Scalar), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        let combiner = PreparedCombiner::new(P_joint, P_client)?;
+        Self::combine_prepared_with_variant::<CtxDigest>(
+            variant,
+            &combiner,
+            message,
+            client_message_1,
+            client_message_2,
+            server_message_1,
+            server_message_2,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn combine_prepared_with_variant<CtxDigest>(
+        variant: SigningVariant,
+        combiner: &PreparedCombiner,
+        message: &[u8],
+        client_message_1: &SignClientRound1,
+        client_message_2: &SignClientRound2,
+        server_message_1: &SignServerRound1,
+        server_message_2: &SignServerRound2,
+    ) -> Result<(CompressedEdwardsY, Scalar), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        #[cfg(feature = "fault-injection")]
+        crate::fault::take_sign_fault(crate::fault::SignStep::ServerCombineSigs)?;
+
+        if client_message_1.session_nonce != server_message_1.session_nonce {
+            return Err(SignError::StaleCommitment);
+        }
+
+        // A zero partial signature is never produced by an honest party and signals a broken peer
+        if client_message_2.z_client == Scalar::ZERO || server_message_2.z_server == Scalar::ZERO {
+            return Err(SignError::ZeroPartialSignature);
+        }
+
+        let rho_client = client_binding_factor::<CtxDigest>(message, client_message_1);
 
         let D_client = client_message_1
             .D_client
@@ -149,14 +748,9 @@ impl ServerSign {
             .E_client
             .decompress()
             .ok_or(SignError::Decompression)?;
-        let R_client = D_client + E_client * rho_client;
+        let R_client = commitment_share(&D_client, &E_client, &rho_client);
 
-        let mut h_server = CtxDigest::new();
-        h_server.update(b"server");
-        h_server.update(message);
-        h_server.update(server_message_1.D_server.as_bytes());
-        h_server.update(server_message_1.E_server.as_bytes());
-        let rho_server = Scalar::from_hash(h_server);
+        let rho_server = server_binding_factor::<CtxDigest>(message, server_message_1);
 
         let D_server = server_message_1
             .D_server
@@ -166,30 +760,218 @@ impl ServerSign {
             .E_server
             .decompress()
             .ok_or(SignError::Decompression)?;
-        let R_server = D_server + E_server * rho_server;
+        let R_server = commitment_share(&D_server, &E_server, &rho_server);
 
-        let R = R_client + R_server;
-        let mut h = CtxDigest::new();
-        h.update(R.compress().as_bytes());
-        h.update(message);
-        h.update(P_joint.as_bytes());
-        let c = Scalar::from_hash(h);
-
-        let expected_1 = EdwardsPoint::mul_base(&client_message_2.z_client);
-        let Y_client = P_client.decompress().ok_or(SignError::Decompression)?;
-        let expected_2 = R_client + Y_client * c;
-
-        // Verify the server's partial signature
-        if expected_1 != expected_2 {
-            return Err(SignError::PartialSignatureVerification);
+        // Checking each party's share of `R` on its own attributes a small-order component to the
+        // party that introduced it
+        if !R_client.is_torsion_free() {
+            return Err(SignError::WeakPartyCommitment {
+                party: Party::Client,
+            });
+        }
+        if !R_server.is_torsion_free() {
+            return Err(SignError::WeakPartyCommitment {
+                party: Party::Server,
+            });
         }
 
+        let R = R_client + R_server;
         let R_joint = R.compress();
+
+        // Verify the client's partial signature
+        verify_partial_decompressed::<CtxDigest>(
+            Party::Client,
+            variant,
+            &R_joint,
+            &R_client.compress(),
+            &combiner.P_peer,
+            message,
+            &combiner.P_joint,
+            &client_message_2.z_client,
+        )?;
+
         let z_joint = client_message_2.z_client + server_message_2.z_server;
 
-        // The final signature is `(R_joint, z_joint)`, which can be encoded as a standard  ed25519
-        // signature
+        // The final signature is `(R_joint, z_joint)`, which `to_signature_bytes` encodes as a
+        // standard ed25519 signature
 
         Ok((R_joint, z_joint))
     }
 }
+
+/// The distributed signing protocol with the digest fixed to SHA-512, as in Ed25519
+#[allow(non_snake_case)]
+impl ServerSign {
+    /// `second_round` with SHA-512
+    pub fn second_round_sha512(
+        p_server: &Scalar,
+        P_joint: &CompressedEdwardsY,
+        message: &[u8],
+        d_server: &Scalar,
+        e_server: &Scalar,
+        client_message: &SignClientRound1,
+        server_message: &SignServerRound1,
+    ) -> Result<(EdwardsPoint, SignServerRound2), SignError> {
+        Self::second_round::<Sha512>(
+            p_server,
+            P_joint,
+            message,
+            d_server,
+            e_server,
+            client_message,
+            server_message,
+        )
+    }
+
+    /// `combine_sigs` with SHA-512
+    pub fn combine_sigs_sha512(
+        P_joint: &CompressedEdwardsY,
+        P_client: &CompressedEdwardsY,
+        message: &[u8],
+        client_message_1: &SignClientRound1,
+        client_message_2: &SignClientRound2,
+        server_message_1: &SignServerRound1,
+        server_message_2: &SignServerRound2,
+    ) -> Result<(CompressedEdwardsY, Scalar), SignError> {
+        Self::combine_sigs::<Sha512>(
+            P_joint,
+            P_client,
+            message,
+            client_message_1,
+            client_message_2,
+            server_message_1,
+            server_message_2,
+        )
+    }
+}
+
+/// The server's state after the first round of the distributed signing protocol
+///
+/// The state holds the server's nonces and is consumed by `second_round`, so the nonces cannot be
+/// used for a second signature. They are zeroized when the state is dropped or aborted.
+///
+/// Signing a second message with the same state does not compile:
+///
+/// ```compile_fail,E0382
+/// use frost::{key_share::KeyShare, sign::client::SignClientRound1, sign::server::ServerSignRound1State};
+/// use sha2::Sha512;
+///
+/// fn sign_twice(key_share: &KeyShare, client_message: &SignClientRound1) {
+///     let state = ServerSignRound1State::first_round();
+///     let _ = state.second_round::<Sha512>(key_share, b"first message", client_message);
+///     let _ = state.second_round::<Sha512>(key_share, b"second message", client_message);
+/// }
+/// ```
+pub struct ServerSignRound1State {
+    pub(crate) d_server: Scalar,
+    pub(crate) e_server: Scalar,
+    server_message: SignServerRound1,
+}
+
+#[allow(non_snake_case)]
+impl ServerSignRound1State {
+    /// Runs the server's first round of the distributed signing protocol
+    pub fn first_round() -> Self {
+        Self::first_round_with_rng(&mut OsRng)
+    }
+
+    /// Runs the server's first round of the distributed signing protocol, drawing its randomness
+    /// from `rng`
+    pub fn first_round_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let (d_server, e_server, server_message) = ServerSign::first_round_with_rng(rng);
+        Self {
+            d_server,
+            e_server,
+            server_message,
+        }
+    }
+
+    /// The round 1 message to send to the client
+    pub fn message(&self) -> &SignServerRound1 {
+        &self.server_message
+    }
+
+    /// Runs the server's second round of the distributed signing protocol over `message`
+    ///
+    /// The state is consumed even if the round fails, so that the nonces are never reused.
+    pub fn second_round<CtxDigest>(
+        self,
+        key_share: &KeyShare,
+        message: &[u8],
+        client_message: &SignClientRound1,
+    ) -> Result<(ServerSignRound2State, SignServerRound2), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        let (_, server_message_2) = ServerSign::second_round_with_share::<CtxDigest>(
+            key_share,
+            message,
+            &self.d_server,
+            &self.e_server,
+            client_message,
+            &self.server_message,
+        )?;
+
+        let state = ServerSignRound2State {
+            P_joint: key_share.joint_public().compress(),
+            P_client: key_share.peer_public().compress(),
+            message: message.to_vec(),
+            server_message_1: self.server_message,
+            server_message_2,
+            client_message_1: *client_message,
+        };
+
+        Ok((state, server_message_2))
+    }
+
+    /// Cancels the session and zeroizes the nonces right away
+    pub fn abort(mut self) {
+        self.zeroize();
+    }
+}
+
+impl Zeroize for ServerSignRound1State {
+    fn zeroize(&mut self) {
+        self.d_server.zeroize();
+        self.e_server.zeroize();
+    }
+}
+
+impl Drop for ServerSignRound1State {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// The server's state after the second round of the distributed signing protocol, which only
+/// holds public values
+#[allow(non_snake_case)]
+pub struct ServerSignRound2State {
+    P_joint: CompressedEdwardsY,
+    P_client: CompressedEdwardsY,
+    message: Vec<u8>,
+    server_message_1: SignServerRound1,
+    server_message_2: SignServerRound2,
+    client_message_1: SignClientRound1,
+}
+
+impl ServerSignRound2State {
+    /// Combines the partial signatures into the signature over the message of the session
+    pub fn combine_sigs<CtxDigest>(
+        &self,
+        client_message_2: &SignClientRound2,
+    ) -> Result<(CompressedEdwardsY, Scalar), SignError>
+    where
+        CtxDigest: Digest<OutputSize = U64>,
+    {
+        ServerSign::combine_sigs::<CtxDigest>(
+            &self.P_joint,
+            &self.P_client,
+            &self.message,
+            &self.client_message_1,
+            client_message_2,
+            &self.server_message_1,
+            &self.server_message_2,
+        )
+    }
+}
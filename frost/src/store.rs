@@ -0,0 +1,57 @@
+//! Pluggable storage of a party's secret key share
+//!
+//! The signing functions take the share as a `&Scalar`. Running them inside `use_share` keeps the
+//! share inside the store for the duration of the call, so that an application never has to hold
+//! it in a long-lived variable of its own.
+
+use curve25519_dalek::scalar::Scalar;
+use zeroize::Zeroize;
+
+/// A store that holds a party's secret key share, such as an HSM, an encrypted file, or memory
+pub trait SecretStore {
+    /// Replaces the share held by the store with `share`
+    fn store(&mut self, share: Scalar);
+
+    /// Returns a copy of the share held by the store
+    fn load(&self) -> Scalar;
+
+    /// Runs `f` on the share held by the store
+    fn use_share<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Scalar) -> R;
+}
+
+/// A secret store that keeps the share in memory and zeroizes it when dropped
+pub struct MemorySecretStore {
+    share: Scalar,
+}
+
+impl MemorySecretStore {
+    pub fn new(share: Scalar) -> Self {
+        Self { share }
+    }
+}
+
+impl SecretStore for MemorySecretStore {
+    fn store(&mut self, share: Scalar) {
+        self.share.zeroize();
+        self.share = share;
+    }
+
+    fn load(&self) -> Scalar {
+        self.share
+    }
+
+    fn use_share<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Scalar) -> R,
+    {
+        f(&self.share)
+    }
+}
+
+impl Drop for MemorySecretStore {
+    fn drop(&mut self) {
+        self.share.zeroize();
+    }
+}
@@ -0,0 +1,161 @@
+//! Runs the protocol over a connection to the other party
+//!
+//! `AsyncTransport` is the connection, which carries the byte encodings of the round messages.
+//! `run_dkg` and `run_sign` run one party's side of the distributed key generation and the
+//! distributed signing protocol over it. The transport does not depend on an async runtime, so it
+//! can be implemented over a tokio socket, a WebSocket, or an in-memory channel alike.
+//!
+//! Both parties send their message of a round before they receive their peer's, so `send` must
+//! not wait for the peer to receive the message.
+
+use std::future::Future;
+
+use curve25519_dalek::{
+    digest::{generic_array::typenum::U64, Digest},
+    edwards::CompressedEdwardsY,
+    scalar::Scalar,
+};
+use thiserror::Error;
+
+use crate::{
+    dkg::{client::*, server::*, DkgError},
+    key_share::KeyShare,
+    sign::{client::*, server::*, SignError},
+    FrostError, Party,
+};
+
+/// A connection to the other party of the protocol that carries whole messages
+pub trait AsyncTransport {
+    type Error;
+
+    /// Sends a message to the other party
+    fn send(&mut self, message: Vec<u8>) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Receives the next message from the other party
+    fn recv(&mut self) -> impl Future<Output = Result<Vec<u8>, Self::Error>>;
+}
+
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum TransportError<E> {
+    #[error("the transport failed: {0}")]
+    Transport(E),
+    #[error(transparent)]
+    Protocol(#[from] FrostError),
+}
+
+impl<E> From<DkgError> for TransportError<E> {
+    fn from(error: DkgError) -> Self {
+        Self::Protocol(error.into())
+    }
+}
+
+impl<E> From<SignError> for TransportError<E> {
+    fn from(error: SignError) -> Self {
+        Self::Protocol(error.into())
+    }
+}
+
+async fn send<T: AsyncTransport>(
+    transport: &mut T,
+    message: Vec<u8>,
+) -> Result<(), TransportError<T::Error>> {
+    transport
+        .send(message)
+        .await
+        .map_err(TransportError::Transport)
+}
+
+async fn recv<T: AsyncTransport>(transport: &mut T) -> Result<Vec<u8>, TransportError<T::Error>> {
+    transport.recv().await.map_err(TransportError::Transport)
+}
+
+/// Runs `party`'s side of the distributed key generation protocol over `transport` and returns
+/// its key share
+#[allow(non_snake_case)]
+pub async fn run_dkg<CtxDigest, T>(
+    party: Party,
+    transport: &mut T,
+) -> Result<KeyShare, TransportError<T::Error>>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+    T: AsyncTransport,
+{
+    let output = match party {
+        Party::Client => {
+            let (c0, c1, C0, C1, client_message_1) = ClientDkg::start_first_round::<CtxDigest>();
+            send(transport, client_message_1.to_bytes()).await?;
+            let server_message_1 = DkgServerRound1::from_bytes(&recv(transport).await?)?;
+            ClientDkg::finalize_first_round::<CtxDigest>(&server_message_1)?;
+
+            let (c_client, client_message_2) = ClientDkg::start_second_round(&c0, &c1);
+            send(transport, client_message_2.to_bytes()).await?;
+            let server_message_2 = DkgServerRound2::from_bytes(&recv(transport).await?)?;
+            ClientDkg::finalize_second_round(
+                &c_client,
+                &C0,
+                &C1,
+                &server_message_1,
+                &server_message_2,
+            )?
+        }
+        Party::Server => {
+            let (s0, s1, S0, S1, server_message_1) = ServerDkg::start_first_round::<CtxDigest>();
+            send(transport, server_message_1.to_bytes()).await?;
+            let client_message_1 = DkgClientRound1::from_bytes(&recv(transport).await?)?;
+            ServerDkg::finalize_first_round::<CtxDigest>(&client_message_1)?;
+
+            let (s_server, server_message_2) = ServerDkg::start_second_round(&s0, &s1);
+            send(transport, server_message_2.to_bytes()).await?;
+            let client_message_2 = DkgClientRound2::from_bytes(&recv(transport).await?)?;
+            ServerDkg::finalize_second_round(
+                &s_server,
+                &S0,
+                &S1,
+                &client_message_1,
+                &client_message_2,
+            )?
+        }
+    };
+
+    Ok(KeyShare::new(output))
+}
+
+/// Runs `party`'s side of the distributed signing protocol over `transport` and returns the
+/// signature `(R, z)` over `message`
+pub async fn run_sign<CtxDigest, T>(
+    party: Party,
+    key_share: &KeyShare,
+    message: &[u8],
+    transport: &mut T,
+) -> Result<(CompressedEdwardsY, Scalar), TransportError<T::Error>>
+where
+    CtxDigest: Digest<OutputSize = U64>,
+    T: AsyncTransport,
+{
+    let signature = match party {
+        Party::Client => {
+            let state = ClientSignRound1State::first_round();
+            send(transport, state.message().to_bytes()).await?;
+            let server_message_1 = SignServerRound1::from_bytes(&recv(transport).await?)?;
+
+            let (state, client_message_2) =
+                state.second_round::<CtxDigest>(key_share, message, &server_message_1)?;
+            send(transport, client_message_2.to_bytes()).await?;
+            let server_message_2 = SignServerRound2::from_bytes(&recv(transport).await?)?;
+            state.combine_sigs::<CtxDigest>(&server_message_2)?
+        }
+        Party::Server => {
+            let state = ServerSignRound1State::first_round();
+            send(transport, state.message().to_bytes()).await?;
+            let client_message_1 = SignClientRound1::from_bytes(&recv(transport).await?)?;
+
+            let (state, server_message_2) =
+                state.second_round::<CtxDigest>(key_share, message, &client_message_1)?;
+            send(transport, server_message_2.to_bytes()).await?;
+            let client_message_2 = SignClientRound2::from_bytes(&recv(transport).await?)?;
+            state.combine_sigs::<CtxDigest>(&client_message_2)?
+        }
+    };
+
+    Ok(signature)
+}
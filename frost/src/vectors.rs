@@ -0,0 +1,179 @@
+//! Test vectors for implementations of the protocol in other languages
+//!
+//! A vector is the complete transcript of a distributed key generation and a signing session run
+//! from a seed with `deterministic_session`, including the intermediate binding factors and the
+//! challenge. The `gen-vectors` example writes them out.
+
+use curve25519_dalek::{edwards::CompressedEdwardsY, scalar::Scalar};
+use sha2::Sha512;
+
+use crate::{
+    deterministic_session,
+    dkg::{client::*, server::*, DkgOutput},
+    sign::{client::*, server::*},
+};
+
+/// Runs the protocol from `seed` over `message` and returns its transcript as JSON
+///
+/// Points and scalars are hex-encoded in their 32-byte wire encodings. The output only depends on
+/// `seed` and `message`.
+#[allow(non_snake_case)]
+pub fn conformance_vectors(seed: [u8; 32], message: &[u8]) -> String {
+    let mut session = deterministic_session(seed);
+
+    let (c0, c1, C0, C1, client_dkg_message_1) =
+        ClientDkg::start_first_round_with_rng::<Sha512>(&mut session.client_rng);
+    let (s0, s1, S0, S1, server_dkg_message_1) =
+        ServerDkg::start_first_round_with_rng::<Sha512>(&mut session.server_rng);
+    let (c_client, client_dkg_message_2) = ClientDkg::start_second_round(&c0, &c1);
+    let (s_server, server_dkg_message_2) = ServerDkg::start_second_round(&s0, &s1);
+
+    let DkgOutput {
+        private_share: p_client,
+        own_public: P_client,
+        peer_public: P_server,
+        joint_public: P_joint,
+    } = ClientDkg::finalize_second_round(
+        &c_client,
+        &C0,
+        &C1,
+        &server_dkg_message_1,
+        &server_dkg_message_2,
+    )
+    .expect("an honest DKG succeeds");
+    let DkgOutput {
+        private_share: p_server,
+        ..
+    } = ServerDkg::finalize_second_round(
+        &s_server,
+        &S0,
+        &S1,
+        &client_dkg_message_1,
+        &client_dkg_message_2,
+    )
+    .expect("an honest DKG succeeds");
+    let P_client = P_client.compress();
+    let P_server = P_server.compress();
+    let P_joint = P_joint.compress();
+
+    let (d_client, e_client, client_sign_message_1) =
+        ClientSign::first_round_with_rng(&mut session.client_rng);
+    let (d_server, e_server, server_sign_message_1) =
+        ServerSign::first_round_with_rng(&mut session.server_rng);
+
+    let client_prepared = ClientSign::prepare_second_round::<Sha512>(
+        &P_joint,
+        message,
+        &client_sign_message_1,
+        &server_sign_message_1,
+    )
+    .expect("honest commitments are accepted");
+    let client_sign_message_2 = ClientSign::finish_with_secret::<Sha512>(
+        &client_prepared,
+        message,
+        &p_client,
+        &d_client,
+        &e_client,
+    )
+    .expect("the message is the prepared one");
+    let server_prepared = ServerSign::prepare_second_round::<Sha512>(
+        &P_joint,
+        message,
+        &client_sign_message_1,
+        &server_sign_message_1,
+    )
+    .expect("honest commitments are accepted");
+    let server_sign_message_2 = ServerSign::finish_with_secret::<Sha512>(
+        &server_prepared,
+        message,
+        &p_server,
+        &d_server,
+        &e_server,
+    )
+    .expect("the message is the prepared one");
+
+    let (R, z) = ClientSign::combine_sigs::<Sha512>(
+        &P_joint,
+        &P_server,
+        message,
+        &client_sign_message_1,
+        &client_sign_message_2,
+        &server_sign_message_1,
+        &server_sign_message_2,
+    )
+    .expect("honest partial signatures combine");
+
+    let fields = [
+        ("seed", hex(&seed)),
+        ("message", hex(message)),
+        ("dkg_client_round1_C0", point(&client_dkg_message_1.C0)),
+        ("dkg_client_round1_C1", point(&client_dkg_message_1.C1)),
+        ("dkg_client_round1_R", point(&client_dkg_message_1.R)),
+        ("dkg_client_round1_mu", scalar(&client_dkg_message_1.mu)),
+        ("dkg_server_round1_S0", point(&server_dkg_message_1.S0)),
+        ("dkg_server_round1_S1", point(&server_dkg_message_1.S1)),
+        ("dkg_server_round1_R", point(&server_dkg_message_1.R)),
+        ("dkg_server_round1_mu", scalar(&server_dkg_message_1.mu)),
+        (
+            "dkg_client_round2_c_server",
+            scalar(&client_dkg_message_2.c_server),
+        ),
+        (
+            "dkg_server_round2_s_client",
+            scalar(&server_dkg_message_2.s_client),
+        ),
+        ("P_client", point(&P_client)),
+        ("P_server", point(&P_server)),
+        ("P_joint", point(&P_joint)),
+        (
+            "sign_client_round1_D",
+            point(&client_sign_message_1.D_client),
+        ),
+        (
+            "sign_client_round1_E",
+            point(&client_sign_message_1.E_client),
+        ),
+        (
+            "sign_server_round1_D",
+            point(&server_sign_message_1.D_server),
+        ),
+        (
+            "sign_server_round1_E",
+            point(&server_sign_message_1.E_server),
+        ),
+        ("rho_client", scalar(&client_prepared.rho)),
+        ("rho_server", scalar(&server_prepared.rho)),
+        ("R_client", point(&client_prepared.party_commitment())),
+        ("R_server", point(&server_prepared.party_commitment())),
+        ("challenge", scalar(&client_prepared.challenge())),
+        (
+            "sign_client_round2_z",
+            scalar(&client_sign_message_2.z_client),
+        ),
+        (
+            "sign_server_round2_z",
+            scalar(&server_sign_message_2.z_server),
+        ),
+        ("signature_R", point(&R)),
+        ("signature_z", scalar(&z)),
+    ];
+
+    let body = fields
+        .iter()
+        .map(|(name, value)| format!("  \"{name}\": \"{value}\""))
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!("{{\n{body}\n}}\n")
+}
+
+fn point(point: &CompressedEdwardsY) -> String {
+    hex(point.as_bytes())
+}
+
+fn scalar(scalar: &Scalar) -> String {
+    hex(scalar.as_bytes())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
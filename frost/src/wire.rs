@@ -0,0 +1,45 @@
+//! Helpers for the fixed byte layouts of the protocol messages
+//!
+//! Points are encoded in their 32-byte compressed form and scalars in their canonical 32-byte
+//! little-endian form. Callers check the length of the input before reading from it.
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use curve25519_dalek::{edwards::CompressedEdwardsY, scalar::Scalar};
+
+/// Reads the point encoded at `bytes[offset..offset + 32]`
+pub(crate) fn point_at(bytes: &[u8], offset: usize) -> CompressedEdwardsY {
+    CompressedEdwardsY(array_at(bytes, offset))
+}
+
+/// Reads the scalar encoded at `bytes[offset..offset + 32]`, or `None` if the encoding is not
+/// canonical
+pub(crate) fn scalar_at(bytes: &[u8], offset: usize) -> Option<Scalar> {
+    Scalar::from_canonical_bytes(array_at(bytes, offset)).into()
+}
+
+/// Reads the 32 bytes at `bytes[offset..offset + 32]`
+pub(crate) fn array_at(bytes: &[u8], offset: usize) -> [u8; 32] {
+    let mut array = [0u8; 32];
+    array.copy_from_slice(&bytes[offset..offset + 32]);
+    array
+}
+
+/// Decodes the concatenated base64 segments written by the `Display` impls of the messages, one
+/// segment for each of `lengths`, or `None` if the string does not consist of exactly these
+/// segments
+pub(crate) fn base64_segments(s: &str, lengths: &[usize]) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(lengths.iter().sum());
+    let mut rest = s;
+    for &length in lengths {
+        let encoded_length = length.div_ceil(3) * 4;
+        let segment = rest.get(..encoded_length)?;
+        let decoded = BASE64_STANDARD.decode(segment).ok()?;
+        if decoded.len() != length {
+            return None;
+        }
+        bytes.extend_from_slice(&decoded);
+        rest = &rest[encoded_length..];
+    }
+
+    rest.is_empty().then_some(bytes)
+}
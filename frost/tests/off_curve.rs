@@ -0,0 +1,277 @@
+//! Every entry point that decompresses a point received from the peer must reject encodings that
+//! are not on the curve with a `Decompression` error instead of proceeding or panicking.
+
+use curve25519_dalek::{
+    constants::ED25519_BASEPOINT_COMPRESSED, edwards::CompressedEdwardsY, scalar::Scalar,
+};
+use frost::{
+    dkg::{client::*, server::*, DkgError},
+    sign::{
+        client::*,
+        receipt::{issue_receipt, verify_receipt},
+        server::*,
+        verify_signature, verify_streaming, SignError,
+    },
+    Party,
+};
+use sha2::Sha512;
+
+/// Encodings of y-coordinates for which there is no x-coordinate on the curve
+fn off_curve_points() -> Vec<CompressedEdwardsY> {
+    let mut points = vec![];
+    for (y, sign) in [(2u8, false), (7, true), (8, false), (11, true)] {
+        let mut bytes = [0u8; 32];
+        bytes[0] = y;
+        if sign {
+            bytes[31] |= 0x80;
+        }
+        let point = CompressedEdwardsY(bytes);
+        assert!(point.decompress().is_none());
+        points.push(point);
+    }
+    points
+}
+
+#[test]
+fn test_dkg_rejects_off_curve_points() {
+    let (_, _, _, _, client_message_1) = ClientDkg::start_first_round::<Sha512>();
+    let (_, _, _, _, server_message_1) = ServerDkg::start_first_round::<Sha512>();
+
+    for point in off_curve_points() {
+        let bad_server_message_1 = DkgServerRound1 {
+            S0: point,
+            ..server_message_1
+        };
+        assert_eq!(
+            ClientDkg::finalize_first_round::<Sha512>(&bad_server_message_1),
+            Err(DkgError::Decompression),
+        );
+
+        let bad_client_message_1 = DkgClientRound1 {
+            C0: point,
+            ..client_message_1
+        };
+        assert_eq!(
+            ServerDkg::finalize_first_round::<Sha512>(&bad_client_message_1),
+            Err(DkgError::Decompression),
+        );
+    }
+}
+
+#[allow(non_snake_case)]
+#[test]
+fn test_dkg_second_round_rejects_off_curve_points() {
+    let (c0, c1, C0, C1, client_message_1) = ClientDkg::start_first_round::<Sha512>();
+    let (s0, s1, S0, S1, server_message_1) = ServerDkg::start_first_round::<Sha512>();
+    let (c_client, client_message_2) = ClientDkg::start_second_round(&c0, &c1);
+    let (s_server, server_message_2) = ServerDkg::start_second_round(&s0, &s1);
+
+    for point in off_curve_points() {
+        for bad_server_message_1 in [
+            DkgServerRound1 {
+                S0: point,
+                ..server_message_1
+            },
+            DkgServerRound1 {
+                S1: point,
+                ..server_message_1
+            },
+        ] {
+            assert_eq!(
+                ClientDkg::finalize_second_round(
+                    &c_client,
+                    &C0,
+                    &C1,
+                    &bad_server_message_1,
+                    &server_message_2,
+                ),
+                Err(DkgError::Decompression),
+            );
+        }
+
+        for bad_client_message_1 in [
+            DkgClientRound1 {
+                C0: point,
+                ..client_message_1
+            },
+            DkgClientRound1 {
+                C1: point,
+                ..client_message_1
+            },
+        ] {
+            assert_eq!(
+                ServerDkg::finalize_second_round(
+                    &s_server,
+                    &S0,
+                    &S1,
+                    &bad_client_message_1,
+                    &client_message_2,
+                ),
+                Err(DkgError::Decompression),
+            );
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[test]
+fn test_signing_rounds_reject_off_curve_points() {
+    let p = Scalar::ONE;
+    let P_joint = ED25519_BASEPOINT_COMPRESSED;
+    let message = b"sample message";
+
+    let (d_client, e_client, client_message_1) = ClientSign::first_round();
+    let (d_server, e_server, server_message_1) = ServerSign::first_round();
+
+    for point in off_curve_points() {
+        let bad_client_messages = [
+            SignClientRound1 {
+                D_client: point,
+                ..client_message_1
+            },
+            SignClientRound1 {
+                E_client: point,
+                ..client_message_1
+            },
+        ];
+        let bad_server_messages = [
+            SignServerRound1 {
+                D_server: point,
+                ..server_message_1
+            },
+            SignServerRound1 {
+                E_server: point,
+                ..server_message_1
+            },
+        ];
+
+        let bad_pairs = bad_client_messages
+            .iter()
+            .map(|client_message| (client_message, &server_message_1))
+            .chain(
+                bad_server_messages
+                    .iter()
+                    .map(|server_message| (&client_message_1, server_message)),
+            );
+
+        for (client_message, server_message) in bad_pairs {
+            assert_eq!(
+                ClientSign::second_round::<Sha512>(
+                    &p,
+                    &P_joint,
+                    message,
+                    &d_client,
+                    &e_client,
+                    client_message,
+                    server_message,
+                ),
+                Err(SignError::Decompression),
+            );
+            assert_eq!(
+                ServerSign::second_round::<Sha512>(
+                    &p,
+                    &P_joint,
+                    message,
+                    &d_server,
+                    &e_server,
+                    client_message,
+                    server_message,
+                ),
+                Err(SignError::Decompression),
+            );
+
+            let client_message_2 = SignClientRound2 { z_client: p };
+            let server_message_2 = SignServerRound2 { z_server: p };
+            assert_eq!(
+                ClientSign::combine_sigs::<Sha512>(
+                    &P_joint,
+                    &P_joint,
+                    message,
+                    client_message,
+                    &client_message_2,
+                    server_message,
+                    &server_message_2,
+                ),
+                Err(SignError::Decompression),
+            );
+            assert_eq!(
+                ServerSign::combine_sigs::<Sha512>(
+                    &P_joint,
+                    &P_joint,
+                    message,
+                    client_message,
+                    &client_message_2,
+                    server_message,
+                    &server_message_2,
+                ),
+                Err(SignError::Decompression),
+            );
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[test]
+fn test_combine_sigs_rejects_off_curve_public_shares() {
+    let message = b"sample message";
+    let (_, _, client_message_1) = ClientSign::first_round();
+    let (_, _, server_message_1) = ServerSign::first_round();
+    let client_message_2 = SignClientRound2 {
+        z_client: Scalar::ONE,
+    };
+    let server_message_2 = SignServerRound2 {
+        z_server: Scalar::ONE,
+    };
+
+    for point in off_curve_points() {
+        assert_eq!(
+            ClientSign::combine_sigs::<Sha512>(
+                &CompressedEdwardsY::default(),
+                &point,
+                message,
+                &client_message_1,
+                &client_message_2,
+                &server_message_1,
+                &server_message_2,
+            ),
+            Err(SignError::Decompression),
+        );
+        assert_eq!(
+            ServerSign::combine_sigs::<Sha512>(
+                &CompressedEdwardsY::default(),
+                &point,
+                message,
+                &client_message_1,
+                &client_message_2,
+                &server_message_1,
+                &server_message_2,
+            ),
+            Err(SignError::Decompression),
+        );
+    }
+}
+
+#[allow(non_snake_case)]
+#[test]
+fn test_verifiers_reject_off_curve_public_keys() {
+    let message = b"sample message";
+    let R = CompressedEdwardsY::default();
+    let z = Scalar::ONE;
+
+    for point in off_curve_points() {
+        assert_eq!(
+            verify_signature::<Sha512>(&point, message, &R, &z),
+            Err(SignError::Decompression),
+        );
+        assert_eq!(
+            verify_streaming::<Sha512>(&point, &message[..], &R, &z),
+            Err(SignError::Decompression),
+        );
+
+        let receipt = issue_receipt::<Sha512>(Party::Client, &z, &point, &R, &z, &[0u8; 32]);
+        assert_eq!(
+            verify_receipt::<Sha512>(&receipt, &point),
+            Err(SignError::Decompression),
+        );
+    }
+}